@@ -1,7 +1,8 @@
 use std::collections::{HashMap, HashSet, BinaryHeap, VecDeque};
 use std::cmp::Ordering;
+use super::index_min_pq::IndexMinPQ;
 
-#[derive(Eq, PartialEq, PartialOrd, Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
 pub struct WeightedEdge {
     v: usize,
     w: usize,
@@ -45,6 +46,25 @@ impl Ord for WeightedEdge {
     }
 }
 
+// by weight like `cmp`, so that `==` agrees with `cmp` returning `Equal`
+impl PartialEq for WeightedEdge {
+
+    fn eq(&self, other: &WeightedEdge) -> bool {
+        self.weight == other.weight
+    }
+}
+
+impl Eq for WeightedEdge {}
+
+// by weight like `cmp`, `BinaryHeap` sifts with `<=`, a derived one would
+// order edges by their vertices first
+impl PartialOrd for WeightedEdge {
+
+    fn partial_cmp(&self, other: &WeightedEdge) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 pub struct EdgeWeightedGraph {
     edges: HashMap<usize, Vec<WeightedEdge>>
 }
@@ -134,3 +154,119 @@ impl LazyMst {
         self.weight
     }
 }
+
+fn index_capacity(graph: &EdgeWeightedGraph) -> usize {
+    graph.edges.keys().max().map_or(0, |v| v + 1)
+}
+
+#[derive(Debug)]
+pub struct EagerMst {
+    edge_to: HashMap<usize, WeightedEdge>,
+    weight: usize
+}
+
+impl EagerMst {
+
+    pub fn new(graph: &EdgeWeightedGraph) -> Result<EagerMst, ()> {
+        let start = match graph.edges.keys().next() {
+            Some(&v) => v,
+            None => return Err(())
+        };
+        let mut marked: HashSet<usize> = HashSet::default();
+        let mut edge_to: HashMap<usize, WeightedEdge> = HashMap::default();
+        let mut pq = IndexMinPQ::new(index_capacity(graph));
+        pq.insert(start, 0);
+        while let Some((v, _)) = pq.pop_min() {
+            marked.insert(v);
+            for e in graph.adjacent_to(v).unwrap() {
+                let w = e.other(v);
+                if marked.contains(&w) {
+                    continue;
+                }
+                if !pq.contains(w) {
+                    pq.insert(w, e.weight());
+                    edge_to.insert(w, *e);
+                }
+                else if e.weight() < *pq.key_of(w).unwrap() {
+                    pq.decrease_key(w, e.weight());
+                    edge_to.insert(w, *e);
+                }
+            }
+        }
+        let weight = edge_to.values().fold(0, |acc, e| acc + e.weight());
+        Ok(EagerMst {
+            edge_to: edge_to,
+            weight: weight
+        })
+    }
+
+    pub fn edges(&self) -> Vec<WeightedEdge> {
+        self.edge_to.values().cloned().collect()
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+}
+
+#[derive(Debug)]
+pub struct ShortestPaths {
+    dist_to: HashMap<usize, usize>,
+    edge_to: HashMap<usize, WeightedEdge>
+}
+
+impl ShortestPaths {
+
+    pub fn new(graph: &EdgeWeightedGraph, source: usize) -> Result<ShortestPaths, ()> {
+        if graph.adjacent_to(source).is_none() {
+            return Err(());
+        }
+        let mut dist_to = HashMap::default();
+        let mut edge_to: HashMap<usize, WeightedEdge> = HashMap::default();
+        let mut pq = IndexMinPQ::new(index_capacity(graph));
+        dist_to.insert(source, 0);
+        pq.insert(source, 0);
+        while let Some((v, dist)) = pq.pop_min() {
+            for e in graph.adjacent_to(v).unwrap() {
+                let w = e.other(v);
+                let through_v = dist + e.weight();
+                let shorter = match dist_to.get(&w) {
+                    Some(&known) => through_v < known,
+                    None => true
+                };
+                if shorter {
+                    dist_to.insert(w, through_v);
+                    edge_to.insert(w, *e);
+                    if pq.contains(w) {
+                        pq.decrease_key(w, through_v);
+                    }
+                    else {
+                        pq.insert(w, through_v);
+                    }
+                }
+            }
+        }
+        Ok(ShortestPaths {
+            dist_to: dist_to,
+            edge_to: edge_to
+        })
+    }
+
+    pub fn dist_to(&self, v: usize) -> Option<usize> {
+        self.dist_to.get(&v).cloned()
+    }
+
+    pub fn path_to(&self, v: usize) -> Option<Vec<usize>> {
+        if !self.dist_to.contains_key(&v) {
+            return None;
+        }
+        let mut path = VecDeque::default();
+        let mut current = v;
+        path.push_front(current);
+        while let Some(e) = self.edge_to.get(&current) {
+            current = e.other(current);
+            path.push_front(current);
+        }
+        Some(path.into_iter().collect())
+    }
+}
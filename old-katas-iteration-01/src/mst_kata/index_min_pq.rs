@@ -0,0 +1,130 @@
+// a binary heap of indices in `0..capacity`, each index carries its own key;
+// `positions` maps an index back to its place in the heap, so a key can be
+// changed or removed in O(log n) instead of leaving stale entries behind
+#[derive(Debug)]
+pub struct IndexMinPQ<K> {
+    heap: Vec<usize>,
+    positions: Vec<Option<usize>>,
+    keys: Vec<Option<K>>
+}
+
+impl<K: Ord> IndexMinPQ<K> {
+
+    pub fn new(capacity: usize) -> IndexMinPQ<K> {
+        IndexMinPQ {
+            heap: Vec::with_capacity(capacity),
+            positions: vec![None; capacity],
+            keys: (0..capacity).map(|_| None).collect()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.capacity() && self.positions[index].is_some()
+    }
+
+    pub fn key_of(&self, index: usize) -> Option<&K> {
+        if index < self.capacity() {
+            self.keys[index].as_ref()
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, key: K) {
+        assert!(index < self.capacity(), "index {} is out of 0..{}", index, self.capacity());
+        assert!(!self.contains(index), "index {} is already in the queue", index);
+        let position = self.heap.len();
+        self.heap.push(index);
+        self.positions[index] = Some(position);
+        self.keys[index] = Some(key);
+        self.swim(position);
+    }
+
+    pub fn decrease_key(&mut self, index: usize, key: K) {
+        let position = self.position_of(index);
+        assert!(&key <= self.keys[index].as_ref().unwrap(), "key of index {} can only decrease", index);
+        self.keys[index] = Some(key);
+        self.swim(position);
+    }
+
+    pub fn peek_min(&self) -> Option<(usize, &K)> {
+        self.heap.first().map(|&index| (index, self.keys[index].as_ref().unwrap()))
+    }
+
+    pub fn pop_min(&mut self) -> Option<(usize, K)> {
+        match self.heap.first() {
+            Some(&index) => self.delete(index).map(|key| (index, key)),
+            None => None
+        }
+    }
+
+    pub fn delete(&mut self, index: usize) -> Option<K> {
+        if !self.contains(index) {
+            return None;
+        }
+        let position = self.position_of(index);
+        let last = self.heap.len() - 1;
+        self.exchange(position, last);
+        self.heap.pop();
+        self.positions[index] = None;
+        if position < self.heap.len() {
+            self.swim(position);
+            self.sink(position);
+        }
+        self.keys[index].take()
+    }
+
+    fn position_of(&self, index: usize) -> usize {
+        match self.positions.get(index) {
+            Some(&Some(position)) => position,
+            _ => panic!("index {} is not in the queue", index)
+        }
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.keys[self.heap[a]] < self.keys[self.heap[b]]
+    }
+
+    fn exchange(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = Some(a);
+        self.positions[self.heap[b]] = Some(b);
+    }
+
+    fn swim(&mut self, mut position: usize) {
+        while position > 0 && self.less(position, (position - 1) / 2) {
+            self.exchange(position, (position - 1) / 2);
+            position = (position - 1) / 2;
+        }
+    }
+
+    fn sink(&mut self, mut position: usize) {
+        loop {
+            let mut child = 2 * position + 1;
+            if child >= self.heap.len() {
+                return;
+            }
+            if child + 1 < self.heap.len() && self.less(child + 1, child) {
+                child += 1;
+            }
+            if !self.less(child, position) {
+                return;
+            }
+            self.exchange(position, child);
+            position = child;
+        }
+    }
+}
@@ -1 +1,2 @@
-pub mod day_1;
\ No newline at end of file
+pub mod day_1;
+pub mod index_min_pq;
@@ -0,0 +1,325 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Num(f64),
+    Op(char),
+    Prefix(char),
+    LeftParen,
+    RightParen,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    UnknownSymbol(String),
+    MismatchedParenthesis,
+    MissingOperand,
+    MissingOperator,
+}
+
+struct Operator {
+    precedence: u8,
+    associativity: Associativity,
+    function: Box<Fn(f64, f64) -> f64>,
+}
+
+// binds tighter than the binary operators of its own precedence, looser than higher ones
+struct PrefixOperator {
+    precedence: u8,
+    function: Box<Fn(f64) -> f64>,
+}
+
+pub struct Operators {
+    table: HashMap<char, Operator>,
+    prefix: HashMap<char, PrefixOperator>,
+}
+
+impl Operators {
+    pub fn empty() -> Self {
+        Operators { table: HashMap::new(), prefix: HashMap::new() }
+    }
+
+    pub fn insert<F>(&mut self, symbol: char, precedence: u8, associativity: Associativity, function: F)
+        where F: Fn(f64, f64) -> f64 + 'static {
+        self.table.insert(symbol, Operator { precedence, associativity, function: Box::new(function) });
+    }
+
+    pub fn insert_prefix<F>(&mut self, symbol: char, precedence: u8, function: F)
+        where F: Fn(f64) -> f64 + 'static {
+        self.prefix.insert(symbol, PrefixOperator { precedence, function: Box::new(function) });
+    }
+
+    pub fn to_rpn(&self, src: &str) -> Result<Vec<Token>, Error> {
+        let mut output = vec![];
+        let mut stack = vec![];
+        let mut expect_operand = true;
+        for token in self.tokenize(src)? {
+            match token {
+                Token::Num(_) if !expect_operand => return Err(Error::MissingOperator),
+                Token::Num(_) => {
+                    output.push(token);
+                    expect_operand = false;
+                }
+                Token::Op(_) if expect_operand => return Err(Error::MissingOperand),
+                Token::Op(symbol) => {
+                    let current = &self.table[&symbol];
+                    while let Some(top) = stack.last().cloned() {
+                        let pops = match top {
+                            Token::Op(top) => {
+                                let top_precedence = self.table[&top].precedence;
+                                top_precedence > current.precedence
+                                    || (top_precedence == current.precedence && current.associativity == Associativity::Left)
+                            }
+                            Token::Prefix(top) => self.prefix[&top].precedence >= current.precedence,
+                            _ => false
+                        };
+                        if !pops {
+                            break;
+                        }
+                        output.push(top);
+                        stack.pop();
+                    }
+                    stack.push(token);
+                    expect_operand = true;
+                }
+                Token::Prefix(_) if !expect_operand => return Err(Error::MissingOperator),
+                Token::Prefix(_) => stack.push(token),
+                Token::LeftParen if !expect_operand => return Err(Error::MissingOperator),
+                Token::LeftParen => stack.push(token),
+                Token::RightParen if expect_operand => return Err(Error::MissingOperand),
+                Token::RightParen => loop {
+                    match stack.pop() {
+                        Some(Token::LeftParen) => break,
+                        Some(op) => output.push(op),
+                        None => return Err(Error::MismatchedParenthesis)
+                    }
+                }
+            }
+        }
+        if expect_operand {
+            return Err(Error::MissingOperand);
+        }
+        while let Some(token) = stack.pop() {
+            if token == Token::LeftParen {
+                return Err(Error::MismatchedParenthesis);
+            }
+            output.push(token);
+        }
+        Ok(output)
+    }
+
+    pub fn parse_rpn(&self, src: &str) -> Result<Vec<Token>, Error> {
+        src.split_whitespace()
+            .map(|word| match word.parse() {
+                Ok(num) => Ok(Token::Num(num)),
+                Err(_) => {
+                    let mut chars = word.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(symbol), None) if self.table.contains_key(&symbol) => Ok(Token::Op(symbol)),
+                        _ => Err(Error::UnknownSymbol(word.to_owned()))
+                    }
+                }
+            })
+            .collect()
+    }
+
+    pub fn evaluate_rpn(&self, tokens: &[Token]) -> Result<f64, Error> {
+        let mut stack = vec![];
+        for token in tokens {
+            match *token {
+                Token::Num(num) => stack.push(num),
+                Token::Op(symbol) => {
+                    let right = stack.pop().ok_or(Error::MissingOperand)?;
+                    let left = stack.pop().ok_or(Error::MissingOperand)?;
+                    let operator = self.table.get(&symbol).ok_or_else(|| Error::UnknownSymbol(symbol.to_string()))?;
+                    stack.push((*operator.function)(left, right));
+                }
+                Token::Prefix(symbol) => {
+                    let operand = stack.pop().ok_or(Error::MissingOperand)?;
+                    let operator = self.prefix.get(&symbol).ok_or_else(|| Error::UnknownSymbol(symbol.to_string()))?;
+                    stack.push((*operator.function)(operand));
+                }
+                Token::LeftParen | Token::RightParen => return Err(Error::MismatchedParenthesis)
+            }
+        }
+        match (stack.pop(), stack.is_empty()) {
+            (Some(result), true) => Ok(result),
+            (Some(_), false) => Err(Error::MissingOperator),
+            (None, _) => Err(Error::MissingOperand)
+        }
+    }
+
+    pub fn evaluate(&self, src: &str) -> Result<f64, Error> {
+        self.to_rpn(src).and_then(|rpn| self.evaluate_rpn(&rpn))
+    }
+
+    fn tokenize(&self, src: &str) -> Result<Vec<Token>, Error> {
+        let mut chars = src.chars().peekable();
+        let mut tokens = vec![];
+        while let Some(c) = chars.peek().cloned() {
+            let operand_position = match tokens.last() {
+                None | Some(&Token::Op(_)) | Some(&Token::Prefix(_)) | Some(&Token::LeftParen) => true,
+                _ => false
+            };
+            match c {
+                c if c.is_whitespace() => { chars.next(); }
+                '(' => { chars.next(); tokens.push(Token::LeftParen); }
+                ')' => { chars.next(); tokens.push(Token::RightParen); }
+                c if operand_position && self.prefix.contains_key(&c) => { chars.next(); tokens.push(Token::Prefix(c)); }
+                c if c.is_digit(10) || c == '.' => tokens.push(Token::Num(parse_num(chars.by_ref())?)),
+                c if self.table.contains_key(&c) => { chars.next(); tokens.push(Token::Op(c)); }
+                c => return Err(Error::UnknownSymbol(c.to_string()))
+            }
+        }
+        Ok(tokens)
+    }
+}
+
+impl Default for Operators {
+    fn default() -> Self {
+        let mut ops = Operators::empty();
+        ops.insert('+', 1, Associativity::Left, |a, b| a + b);
+        ops.insert('-', 1, Associativity::Left, |a, b| a - b);
+        ops.insert('×', 2, Associativity::Left, |a, b| a * b);
+        ops.insert('÷', 2, Associativity::Left, |a, b| a / b);
+        ops.insert('^', 3, Associativity::Right, f64::powf);
+        ops.insert_prefix('-', 2, |a| -a);
+        ops
+    }
+}
+
+fn parse_num(chars: &mut Peekable<Chars>) -> Result<f64, Error> {
+    let mut num = String::new();
+    while let Some(c) = chars.peek().cloned() {
+        if !c.is_digit(10) && c != '.' {
+            break;
+        }
+        num.push(c);
+        chars.next();
+    }
+    num.parse().map_err(|_| Error::UnknownSymbol(num))
+}
+
+pub fn to_rpn(src: &str) -> Result<Vec<Token>, Error> {
+    Operators::default().to_rpn(src)
+}
+
+pub fn evaluate_rpn(src: &str) -> Result<f64, Error> {
+    let ops = Operators::default();
+    ops.parse_rpn(src).and_then(|rpn| ops.evaluate_rpn(&rpn))
+}
+
+pub fn evaluate<'s>(src: Cow<'s, str>) -> Result<f64, Error> {
+    Operators::default().evaluate(&src)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn evaluate_num() {
+        assert_eq!(evaluate(Cow::Borrowed("10")), Ok(10.0));
+    }
+
+    #[test]
+    fn evaluate_many_operations() {
+        assert_eq!(evaluate(Cow::Borrowed("3+2×4-81÷3")), Ok(-16.0));
+    }
+
+    #[test]
+    fn evaluate_with_parenthesis() {
+        assert_eq!(evaluate(Cow::Borrowed("((3 + 2×4) - 81)÷7")), Ok(-10.0));
+    }
+
+    #[test]
+    fn negative_numbers() {
+        assert_eq!(evaluate(Cow::Borrowed("-2×(-3+1)")), Ok(4.0));
+    }
+
+    #[test]
+    fn negate_any_operand() {
+        assert_eq!(evaluate(Cow::Borrowed("-(2+3)")), Ok(-5.0));
+        assert_eq!(evaluate(Cow::Borrowed("--2")), Ok(2.0));
+        assert_eq!(evaluate(Cow::Borrowed("2^-1")), Ok(0.5));
+    }
+
+    #[test]
+    fn negation_binds_looser_than_power_and_tighter_than_product() {
+        assert_eq!(
+            to_rpn("-2^2×3"),
+            Ok(vec![
+                Token::Num(2.0), Token::Num(2.0), Token::Op('^'), Token::Prefix('-'), Token::Num(3.0), Token::Op('×')
+            ])
+        );
+        assert_eq!(evaluate(Cow::Borrowed("-2^2")), Ok(-4.0));
+    }
+
+    #[test]
+    fn any_whitespace_separates_tokens() {
+        assert_eq!(evaluate(Cow::Borrowed("1\t+\n2")), Ok(3.0));
+    }
+
+    #[test]
+    fn infix_to_rpn() {
+        assert_eq!(
+            to_rpn("3+4×2÷(1-5)"),
+            Ok(vec![
+                Token::Num(3.0), Token::Num(4.0), Token::Num(2.0), Token::Op('×'),
+                Token::Num(1.0), Token::Num(5.0), Token::Op('-'), Token::Op('÷'), Token::Op('+')
+            ])
+        );
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(
+            to_rpn("2^3^2"),
+            Ok(vec![Token::Num(2.0), Token::Num(3.0), Token::Num(2.0), Token::Op('^'), Token::Op('^')])
+        );
+        assert_eq!(evaluate(Cow::Borrowed("2^3^2")), Ok(512.0));
+    }
+
+    #[test]
+    fn evaluate_rpn_input() {
+        assert_eq!(evaluate_rpn("3 4 2 × 1 5 - ÷ +"), Ok(1.0));
+        assert_eq!(evaluate_rpn("-2.5 2 ×"), Ok(-5.0));
+    }
+
+    #[test]
+    fn malformed_rpn_input() {
+        assert_eq!(evaluate_rpn("1 +"), Err(Error::MissingOperand));
+        assert_eq!(evaluate_rpn("1 2"), Err(Error::MissingOperator));
+        assert_eq!(evaluate_rpn("1 2 %"), Err(Error::UnknownSymbol("%".to_owned())));
+    }
+
+    #[test]
+    fn malformed_infix_input() {
+        assert_eq!(to_rpn("(1+2"), Err(Error::MismatchedParenthesis));
+        assert_eq!(to_rpn("1+2)"), Err(Error::MismatchedParenthesis));
+        assert_eq!(to_rpn("1+"), Err(Error::MissingOperand));
+        assert_eq!(to_rpn("1 2"), Err(Error::MissingOperator));
+        assert_eq!(to_rpn("1&2"), Err(Error::UnknownSymbol("&".to_owned())));
+    }
+
+    #[test]
+    fn custom_operators() {
+        let mut ops = Operators::default();
+        ops.insert('%', 2, Associativity::Left, |a, b| a % b);
+        ops.insert('↑', 3, Associativity::Left, f64::max);
+
+        assert_eq!(ops.evaluate("1+17%5"), Ok(3.0));
+        assert_eq!(ops.evaluate("2↑7×3"), Ok(21.0));
+        assert_eq!(ops.parse_rpn("17 5 %").and_then(|rpn| ops.evaluate_rpn(&rpn)), Ok(2.0));
+    }
+}
@@ -1,8 +1,8 @@
-pub use tdd_kata::mst_kata::day_1::{EdgeWeightedGraph, WeightedEdge, LazyMst};
+pub use tdd_kata::mst_kata::day_1::{EdgeWeightedGraph, WeightedEdge, LazyMst, EagerMst, ShortestPaths};
 
 pub use std::cmp::Ordering;
 
-pub use expectest::prelude::{be_equal_to, be_some, be_ok, be_err};
+pub use expectest::prelude::{be_equal_to, be_some, be_none, be_ok, be_err};
 
 describe! edge_weighted_graph {
 
@@ -34,6 +34,19 @@ describe! edge_weighted_graph {
 
             expect!(edge_1.cmp(&edge_2)).to(be_equal_to(Ordering::Equal));
         }
+
+        it "should be partially comparable by weight only" {
+            let bigger = WeightedEdge::new(2, 3, 3);
+            let smaller = WeightedEdge::new(1, 2, 1);
+
+            expect!(bigger.partial_cmp(&smaller)).to(be_some().value(Ordering::Less));
+            expect!(bigger < smaller).to(be_equal_to(true));
+        }
+
+        it "should be equal by weight only" {
+            expect!(WeightedEdge::new(1, 2, 2)).to(be_equal_to(WeightedEdge::new(1, 3, 2)));
+            expect!(WeightedEdge::new(1, 2, 2) == WeightedEdge::new(1, 2, 3)).to(be_equal_to(false));
+        }
     }
 
     describe! graph {
@@ -114,5 +127,100 @@ describe! edge_weighted_graph {
 
             expect!(mst.weight()).to(be_equal_to(2));
         }
+
+        it "should pick the lightest crossing edges" {
+            let mut graph = EdgeWeightedGraph::default();
+
+            graph.add_edge(WeightedEdge::new(0, 1, 4));
+            graph.add_edge(WeightedEdge::new(0, 2, 1));
+            graph.add_edge(WeightedEdge::new(2, 1, 2));
+            graph.add_edge(WeightedEdge::new(1, 3, 5));
+            graph.add_edge(WeightedEdge::new(2, 3, 8));
+            graph.add_edge(WeightedEdge::new(3, 4, 3));
+
+            let mst = LazyMst::new(&graph).unwrap();
+
+            expect!(mst.weight()).to(be_equal_to(11));
+        }
+    }
+    describe! eager_mst {
+
+        it "should not create an eager mst from an empty graph" {
+            let graph = EdgeWeightedGraph::default();
+
+            expect!(EagerMst::new(&graph)).to(be_err());
+        }
+
+        it "should be weight of 2" {
+            let mut graph = EdgeWeightedGraph::default();
+
+            graph.add_edge(WeightedEdge::new(1, 2, 1));
+            graph.add_edge(WeightedEdge::new(2, 3, 1));
+            graph.add_edge(WeightedEdge::new(1, 3, 1));
+
+            let mst = EagerMst::new(&graph).unwrap();
+
+            expect!(mst.weight()).to(be_equal_to(2));
+            expect!(mst.edges().len()).to(be_equal_to(2));
+        }
+
+        it "should pick the lightest crossing edges" {
+            let mut graph = EdgeWeightedGraph::default();
+
+            graph.add_edge(WeightedEdge::new(0, 1, 4));
+            graph.add_edge(WeightedEdge::new(0, 2, 1));
+            graph.add_edge(WeightedEdge::new(2, 1, 2));
+            graph.add_edge(WeightedEdge::new(1, 3, 5));
+            graph.add_edge(WeightedEdge::new(2, 3, 8));
+            graph.add_edge(WeightedEdge::new(3, 4, 3));
+
+            let eager = EagerMst::new(&graph).unwrap();
+            let lazy = LazyMst::new(&graph).unwrap();
+
+            expect!(eager.weight()).to(be_equal_to(11));
+            expect!(eager.weight()).to(be_equal_to(lazy.weight()));
+        }
+    }
+
+    describe! shortest_paths {
+
+        before_each {
+            let mut graph = EdgeWeightedGraph::default();
+
+            graph.add_edge(WeightedEdge::new(0, 1, 4));
+            graph.add_edge(WeightedEdge::new(0, 2, 1));
+            graph.add_edge(WeightedEdge::new(2, 1, 2));
+            graph.add_edge(WeightedEdge::new(1, 3, 5));
+            graph.add_edge(WeightedEdge::new(2, 3, 8));
+            graph.add_edge(WeightedEdge::new(3, 4, 3));
+            graph.add_edge(WeightedEdge::new(7, 8, 1));
+        }
+
+        it "should not start from a missing vertex" {
+            expect!(ShortestPaths::new(&graph, 5)).to(be_err());
+        }
+
+        it "should find the shortest distances" {
+            let paths = ShortestPaths::new(&graph, 0).unwrap();
+
+            expect!(paths.dist_to(0)).to(be_some().value(0));
+            expect!(paths.dist_to(1)).to(be_some().value(3));
+            expect!(paths.dist_to(3)).to(be_some().value(8));
+            expect!(paths.dist_to(4)).to(be_some().value(11));
+        }
+
+        it "should find the shortest path" {
+            let paths = ShortestPaths::new(&graph, 0).unwrap();
+
+            expect!(paths.path_to(4)).to(be_some().value(vec![0, 2, 1, 3, 4]));
+            expect!(paths.path_to(0)).to(be_some().value(vec![0]));
+        }
+
+        it "should not reach another component" {
+            let paths = ShortestPaths::new(&graph, 0).unwrap();
+
+            expect!(paths.dist_to(8)).to(be_none());
+            expect!(paths.path_to(8)).to(be_none());
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+pub use tdd_kata::mst_kata::index_min_pq::IndexMinPQ;
+
+pub use expectest::prelude::{be_equal_to, be_some, be_none, be_true, be_false};
+
+describe! index_min_pq {
+
+    before_each {
+        let mut pq: IndexMinPQ<usize> = IndexMinPQ::new(10);
+    }
+
+    it "should create an empty queue" {
+        expect!(pq.is_empty()).to(be_true());
+        expect!(pq.capacity()).to(be_equal_to(10));
+        expect!(pq.pop_min()).to(be_none());
+    }
+
+    it "should insert keys by index" {
+        pq.insert(3, 30);
+        pq.insert(7, 10);
+
+        expect!(pq.len()).to(be_equal_to(2));
+        expect!(pq.contains(3)).to(be_true());
+        expect!(pq.contains(5)).to(be_false());
+        expect!(pq.contains(42)).to(be_false());
+        expect!(pq.key_of(3)).to(be_some().value(&30));
+        expect!(pq.peek_min()).to(be_some().value((7, &10)));
+    }
+
+    it "should pop indices in key order" {
+        for (index, key) in vec![(0, 5), (1, 3), (2, 8), (3, 1), (4, 9), (5, 2)] {
+            pq.insert(index, key);
+        }
+
+        let mut popped = vec![];
+        while let Some(min) = pq.pop_min() {
+            popped.push(min);
+        }
+
+        expect!(popped).to(be_equal_to(vec![(3, 1), (5, 2), (1, 3), (0, 5), (2, 8), (4, 9)]));
+        expect!(pq.contains(3)).to(be_false());
+    }
+
+    it "should decrease a key" {
+        pq.insert(1, 10);
+        pq.insert(2, 20);
+        pq.insert(3, 30);
+
+        pq.decrease_key(3, 5);
+
+        expect!(pq.key_of(3)).to(be_some().value(&5));
+        expect!(pq.pop_min()).to(be_some().value((3, 5)));
+        expect!(pq.pop_min()).to(be_some().value((1, 10)));
+    }
+
+    it "should delete an index" {
+        pq.insert(1, 10);
+        pq.insert(2, 20);
+        pq.insert(3, 30);
+        pq.insert(4, 40);
+
+        expect!(pq.delete(2)).to(be_some().value(20));
+        expect!(pq.delete(2)).to(be_none());
+
+        expect!(pq.len()).to(be_equal_to(3));
+        expect!(pq.pop_min()).to(be_some().value((1, 10)));
+        expect!(pq.pop_min()).to(be_some().value((3, 30)));
+        expect!(pq.pop_min()).to(be_some().value((4, 40)));
+    }
+
+    it "should reuse a popped index" {
+        pq.insert(1, 10);
+        pq.pop_min();
+
+        pq.insert(1, 5);
+
+        expect!(pq.pop_min()).to(be_some().value((1, 5)));
+    }
+
+    failing "should not insert the same index twice" {
+        pq.insert(1, 10);
+        pq.insert(1, 20);
+    }
+
+    failing "should not insert past capacity" {
+        pq.insert(10, 1);
+    }
+
+    failing "should not increase a key" {
+        pq.insert(1, 10);
+        pq.decrease_key(1, 20);
+    }
+
+    failing "should not decrease a key of a missing index" {
+        pq.decrease_key(1, 10);
+    }
+}
@@ -1 +1,2 @@
-mod day_1;
\ No newline at end of file
+mod day_1;
+mod index_min_pq;
@@ -0,0 +1,328 @@
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::Chars;
+
+#[derive(PartialEq, Debug)]
+pub enum Ast {
+    Num(f64),
+    Operation(char, Box<Ast>, Box<Ast>),
+    // keeps whatever was parsed around the problem, e.g. both sides of a missing operator
+    Error(Vec<Ast>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ErrorKind {
+    InvalidNumber(String),
+    Unexpected(String),
+    MissingOperand,
+    MissingOperator,
+    UnclosedParenthesis,
+    UnmatchedParenthesis,
+}
+
+// spans are in chars, the way an editor counts columns in a formula
+#[derive(PartialEq, Debug)]
+pub struct Diagnostic {
+    pub kind: ErrorKind,
+    pub span: Range<usize>,
+}
+
+pub struct Parser<'p> {
+    iter: Peekable<Chars<'p>>,
+    position: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'p> Parser<'p> {
+    pub fn new(src: &'p str) -> Self {
+        Parser { iter: src.chars().peekable(), position: 0, diagnostics: Vec::new() }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn parse_expression(&mut self) -> Ast {
+        self.parse_group(false)
+    }
+
+    fn parse_group(&mut self, nested: bool) -> Ast {
+        let mut root = self.parse_sum();
+        loop {
+            let next = self.peek();
+            let start = self.position;
+            match next {
+                None => return root,
+                Some(')') if nested => return root,
+                Some(')') => {
+                    self.next();
+                    self.report(ErrorKind::UnmatchedParenthesis, start..self.position);
+                    root = self.continue_sum(root);
+                }
+                Some(_) => {
+                    self.report(ErrorKind::MissingOperator, start..start);
+                    root = Ast::Error(vec![root, self.parse_sum()]);
+                }
+            }
+        }
+    }
+
+    fn parse_sum(&mut self) -> Ast {
+        let root = self.parse_term();
+        self.continue_sum(root)
+    }
+
+    fn continue_sum(&mut self, root: Ast) -> Ast {
+        let mut root = self.continue_term(root);
+        while let Some(operand) = self.low_priority_operand() {
+            root = Ast::Operation(operand, Box::new(root), Box::new(self.parse_term()))
+        }
+        root
+    }
+
+    fn low_priority_operand(&mut self) -> Option<char> {
+        match self.peek() {
+            Some('+') | Some('-') => self.next(),
+            _ => None
+        }
+    }
+
+    fn parse_term(&mut self) -> Ast {
+        let root = self.parse_factor();
+        self.continue_term(root)
+    }
+
+    fn continue_term(&mut self, mut root: Ast) -> Ast {
+        while let Some(operand) = self.high_priority_operand() {
+            root = Ast::Operation(operand, Box::new(root), Box::new(self.parse_factor()))
+        }
+        root
+    }
+
+    fn high_priority_operand(&mut self) -> Option<char> {
+        match self.peek() {
+            Some('×') | Some('÷') => self.next(),
+            _ => None
+        }
+    }
+
+    fn parse_factor(&mut self) -> Ast {
+        let next = self.peek();
+        let start = self.position;
+        match next {
+            Some('(') => {
+                self.next();
+                let sub_root = self.parse_group(true);
+                match self.peek() {
+                    Some(')') => { self.next(); }
+                    _ => self.report(ErrorKind::UnclosedParenthesis, start..start + 1)
+                }
+                sub_root
+            }
+            Some('-') => {
+                self.next();
+                match self.parse_factor() {
+                    Ast::Num(num) => Ast::Num(-num),
+                    error @ Ast::Error(_) => error,
+                    operand => Ast::Operation('-', Box::new(Ast::Num(0.0)), Box::new(operand))
+                }
+            }
+            Some(character) if !is_delimiter(character) => {
+                let mut word = String::new();
+                while let Some(character) = self.iter.peek().cloned() {
+                    if is_delimiter(character) {
+                        break;
+                    }
+                    word.push(character);
+                    self.next();
+                }
+                match word.parse() {
+                    Ok(num) => Ast::Num(num),
+                    Err(_) => {
+                        let kind = if character.is_ascii_digit() || character == '.' {
+                            ErrorKind::InvalidNumber(word)
+                        } else {
+                            ErrorKind::Unexpected(word)
+                        };
+                        self.report(kind, start..self.position);
+                        Ast::Error(vec![])
+                    }
+                }
+            }
+            _ => {
+                self.report(ErrorKind::MissingOperand, start..start);
+                Ast::Error(vec![])
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        while let Some(character) = self.iter.peek().cloned() {
+            if !character.is_whitespace() {
+                return Some(character);
+            }
+            self.next();
+        }
+        None
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let next = self.iter.next();
+        if next.is_some() {
+            self.position += 1;
+        }
+        next
+    }
+
+    fn report(&mut self, kind: ErrorKind, span: Range<usize>) {
+        self.diagnostics.push(Diagnostic { kind, span });
+    }
+}
+
+fn is_delimiter(character: char) -> bool {
+    character.is_whitespace() || "+-×÷()".contains(character)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(num: f64) -> Ast {
+        Ast::Num(num)
+    }
+
+    fn operation(operand: char, left: Ast, right: Ast) -> Ast {
+        Ast::Operation(operand, Box::new(left), Box::new(right))
+    }
+
+    fn diagnostic(kind: ErrorKind, span: Range<usize>) -> Diagnostic {
+        Diagnostic { kind, span }
+    }
+
+    fn parse(src: &str) -> (Ast, Vec<Diagnostic>) {
+        let mut parser = Parser::new(src);
+        let ast = parser.parse_expression();
+        (ast, parser.diagnostics)
+    }
+
+    #[test]
+    fn parse_negative_number() {
+        assert_eq!(parse("-4"), (num(-4.0), vec![]));
+    }
+
+    #[test]
+    fn parse_many_operations() {
+        assert_eq!(
+            parse("3÷1 + 2×3÷3 - 1").0,
+            operation(
+                '-',
+                operation(
+                    '+',
+                    operation('÷', num(3.0), num(1.0)),
+                    operation('÷', operation('×', num(2.0), num(3.0)), num(3.0))
+                ),
+                num(1.0)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_expression_with_parenthesis() {
+        assert_eq!(
+            parse("3÷(1+2)×-(3-1)"),
+            (
+                operation(
+                    '×',
+                    operation('÷', num(3.0), operation('+', num(1.0), num(2.0))),
+                    operation('-', num(0.0), operation('-', num(3.0), num(1.0)))
+                ),
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn missing_operand_becomes_error_node() {
+        assert_eq!(
+            parse("3+"),
+            (operation('+', num(3.0), Ast::Error(vec![])), vec![diagnostic(ErrorKind::MissingOperand, 2..2)])
+        );
+    }
+
+    #[test]
+    fn invalid_number_and_unexpected_input() {
+        assert_eq!(
+            parse("1.2.3 × x"),
+            (
+                operation('×', Ast::Error(vec![]), Ast::Error(vec![])),
+                vec![
+                    diagnostic(ErrorKind::InvalidNumber("1.2.3".to_owned()), 0..5),
+                    diagnostic(ErrorKind::Unexpected("x".to_owned()), 8..9)
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn unclosed_parenthesis_spans_the_opening_one() {
+        assert_eq!(
+            parse("2×(1+3"),
+            (
+                operation('×', num(2.0), operation('+', num(1.0), num(3.0))),
+                vec![diagnostic(ErrorKind::UnclosedParenthesis, 2..3)]
+            )
+        );
+    }
+
+    #[test]
+    fn unmatched_parenthesis_is_skipped() {
+        assert_eq!(
+            parse("1+2)×3"),
+            (
+                operation('×', operation('+', num(1.0), num(2.0)), num(3.0)),
+                vec![diagnostic(ErrorKind::UnmatchedParenthesis, 3..4)]
+            )
+        );
+    }
+
+    #[test]
+    fn missing_operator_keeps_both_operands_in_an_error_node() {
+        assert_eq!(
+            parse("(1 2) + 3"),
+            (
+                operation('+', Ast::Error(vec![num(1.0), num(2.0)]), num(3.0)),
+                vec![diagnostic(ErrorKind::MissingOperator, 3..3)]
+            )
+        );
+    }
+
+    #[test]
+    fn stray_operand_takes_the_rest_of_the_sum() {
+        assert_eq!(
+            parse("1 2×3 + 4").0,
+            Ast::Error(vec![num(1.0), operation('+', operation('×', num(2.0), num(3.0)), num(4.0))])
+        );
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let (ast, diagnostics) = parse("(1 + ) × 2x ÷ (4");
+
+        assert_eq!(
+            ast,
+            operation(
+                '÷',
+                operation('×', operation('+', num(1.0), Ast::Error(vec![])), Ast::Error(vec![])),
+                num(4.0)
+            )
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                diagnostic(ErrorKind::MissingOperand, 5..5),
+                diagnostic(ErrorKind::InvalidNumber("2x".to_owned()), 9..11),
+                diagnostic(ErrorKind::UnclosedParenthesis, 14..15)
+            ]
+        );
+    }
+}
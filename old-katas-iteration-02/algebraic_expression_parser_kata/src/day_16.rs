@@ -0,0 +1,283 @@
+use lexer::{Lexer, Token, TokenKind};
+use std::iter::Peekable;
+use std::ops::Range;
+
+#[derive(PartialEq, Debug)]
+pub enum Ast {
+    Num(f64),
+    Var(String),
+    Operation(char, Box<Ast>, Box<Ast>),
+    // keeps whatever was parsed around the problem, e.g. both sides of a missing operator
+    Error(Vec<Ast>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ErrorKind {
+    Invalid(String),
+    MissingOperand,
+    MissingOperator,
+    UnclosedParenthesis,
+    UnmatchedParenthesis,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Diagnostic {
+    pub kind: ErrorKind,
+    pub span: Range<usize>,
+}
+
+pub struct Parser<'p> {
+    tokens: Peekable<Lexer<'p>>,
+    end: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'p> Parser<'p> {
+    pub fn new(src: &'p str) -> Self {
+        Parser { tokens: Lexer::new(src).peekable(), end: src.chars().count(), diagnostics: Vec::new() }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn parse_expression(&mut self) -> Ast {
+        self.parse_group(false)
+    }
+
+    fn parse_group(&mut self, nested: bool) -> Ast {
+        let mut root = self.parse_sum();
+        loop {
+            let start = self.start();
+            match self.take_ahead() {
+                None => return root,
+                Some(TokenKind::RightParen) if nested => return root,
+                Some(TokenKind::RightParen) => {
+                    self.skip();
+                    self.report(ErrorKind::UnmatchedParenthesis, start..start + 1);
+                    root = self.continue_sum(root);
+                }
+                Some(_) => {
+                    self.report(ErrorKind::MissingOperator, start..start);
+                    root = Ast::Error(vec![root, self.parse_sum()]);
+                }
+            }
+        }
+    }
+
+    fn parse_sum(&mut self) -> Ast {
+        let root = self.parse_term();
+        self.continue_sum(root)
+    }
+
+    fn continue_sum(&mut self, root: Ast) -> Ast {
+        let mut root = self.continue_term(root);
+        while let Some(operand) = self.low_priority_operand() {
+            root = Ast::Operation(operand, Box::new(root), Box::new(self.parse_term()))
+        }
+        root
+    }
+
+    fn low_priority_operand(&mut self) -> Option<char> {
+        let operand = match self.take_ahead() {
+            Some(TokenKind::Op("+")) => '+',
+            Some(TokenKind::Op("-")) => '-',
+            _ => return None
+        };
+        self.skip();
+        Some(operand)
+    }
+
+    fn parse_term(&mut self) -> Ast {
+        let root = self.parse_factor();
+        self.continue_term(root)
+    }
+
+    fn continue_term(&mut self, mut root: Ast) -> Ast {
+        while let Some(operand) = self.high_priority_operand() {
+            root = Ast::Operation(operand, Box::new(root), Box::new(self.parse_factor()))
+        }
+        root
+    }
+
+    fn high_priority_operand(&mut self) -> Option<char> {
+        let operand = match self.take_ahead() {
+            Some(TokenKind::Op("*")) | Some(TokenKind::Op("×")) => '×',
+            Some(TokenKind::Op("/")) | Some(TokenKind::Op("÷")) => '÷',
+            Some(TokenKind::Op("%")) => '%',
+            _ => return None
+        };
+        self.skip();
+        Some(operand)
+    }
+
+    fn parse_factor(&mut self) -> Ast {
+        if self.take_ahead() != Some(TokenKind::Op("-")) {
+            return self.parse_power();
+        }
+        self.skip();
+        match self.parse_factor() {
+            Ast::Num(num) => Ast::Num(-num),
+            error @ Ast::Error(_) => error,
+            operand => Ast::Operation('-', Box::new(Ast::Num(0.0)), Box::new(operand))
+        }
+    }
+
+    fn parse_power(&mut self) -> Ast {
+        let root = self.parse_atom();
+        if self.take_ahead() != Some(TokenKind::Op("**")) {
+            return root;
+        }
+        self.skip();
+        Ast::Operation('^', Box::new(root), Box::new(self.parse_factor()))
+    }
+
+    fn parse_atom(&mut self) -> Ast {
+        let start = self.start();
+        match self.take_ahead() {
+            Some(TokenKind::LeftParen) => {
+                self.skip();
+                let sub_root = self.parse_group(true);
+                match self.take_ahead() {
+                    Some(TokenKind::RightParen) => self.skip(),
+                    _ => self.report(ErrorKind::UnclosedParenthesis, start..start + 1)
+                }
+                sub_root
+            }
+            Some(TokenKind::Num(num)) => {
+                self.skip();
+                Ast::Num(num.parse().expect("lexer produces valid numbers"))
+            }
+            Some(TokenKind::Ident(name)) => {
+                self.skip();
+                Ast::Var(name)
+            }
+            Some(TokenKind::Invalid(text)) => {
+                let Token { span, .. } = self.tokens.next().unwrap();
+                self.report(ErrorKind::Invalid(text), span);
+                Ast::Error(vec![])
+            }
+            _ => {
+                self.report(ErrorKind::MissingOperand, start..start);
+                Ast::Error(vec![])
+            }
+        }
+    }
+
+    fn take_ahead(&mut self) -> Option<TokenKind> {
+        self.tokens.peek().map(|token| token.kind.clone())
+    }
+
+    fn start(&mut self) -> usize {
+        let end = self.end;
+        self.tokens.peek().map_or(end, |token| token.span.start)
+    }
+
+    fn skip(&mut self) {
+        self.tokens.next();
+    }
+
+    fn report(&mut self, kind: ErrorKind, span: Range<usize>) {
+        self.diagnostics.push(Diagnostic { kind, span });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(num: f64) -> Ast {
+        Ast::Num(num)
+    }
+
+    fn var(name: &str) -> Ast {
+        Ast::Var(name.to_owned())
+    }
+
+    fn operation(operand: char, left: Ast, right: Ast) -> Ast {
+        Ast::Operation(operand, Box::new(left), Box::new(right))
+    }
+
+    fn diagnostic(kind: ErrorKind, span: Range<usize>) -> Diagnostic {
+        Diagnostic { kind, span }
+    }
+
+    fn parse(src: &str) -> (Ast, Vec<Diagnostic>) {
+        let mut parser = Parser::new(src);
+        let ast = parser.parse_expression();
+        (ast, parser.diagnostics)
+    }
+
+    #[test]
+    fn parse_expression_with_parenthesis() {
+        assert_eq!(
+            parse("3÷(1+2)×-(3-1)"),
+            (
+                operation(
+                    '×',
+                    operation('÷', num(3.0), operation('+', num(1.0), num(2.0))),
+                    operation('-', num(0.0), operation('-', num(3.0), num(1.0)))
+                ),
+                vec![]
+            )
+        );
+    }
+
+    #[test]
+    fn ascii_operators_and_whitespace() {
+        assert_eq!(parse("3 / 1 + 2 * x").0, parse("3÷1+2×x").0);
+        assert_eq!(parse("7 % x * 2").0, operation('×', operation('%', num(7.0), var("x")), num(2.0)));
+    }
+
+    #[test]
+    fn scientific_hex_and_separated_numbers() {
+        assert_eq!(
+            parse("1e-3 + 0x1F * 1_000"),
+            (operation('+', num(0.001), operation('×', num(31.0), num(1000.0))), vec![])
+        );
+    }
+
+    #[test]
+    fn power_is_right_associative_and_binds_tighter_than_negation() {
+        assert_eq!(
+            parse("-2 ** 3 ** rate").0,
+            operation('-', num(0.0), operation('^', num(2.0), operation('^', num(3.0), var("rate"))))
+        );
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let (ast, diagnostics) = parse("(1 + ) × 2x ÷ (4");
+
+        assert_eq!(
+            ast,
+            operation(
+                '÷',
+                operation('×', operation('+', num(1.0), Ast::Error(vec![])), Ast::Error(vec![])),
+                num(4.0)
+            )
+        );
+        assert_eq!(
+            diagnostics,
+            vec![
+                diagnostic(ErrorKind::MissingOperand, 5..5),
+                diagnostic(ErrorKind::Invalid("2x".to_owned()), 9..11),
+                diagnostic(ErrorKind::UnclosedParenthesis, 14..15)
+            ]
+        );
+    }
+
+    #[test]
+    fn unmatched_parenthesis_and_missing_operator() {
+        assert_eq!(
+            parse("1 + 2) x"),
+            (
+                Ast::Error(vec![operation('+', num(1.0), num(2.0)), var("x")]),
+                vec![
+                    diagnostic(ErrorKind::UnmatchedParenthesis, 5..6),
+                    diagnostic(ErrorKind::MissingOperator, 7..7)
+                ]
+            )
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(PartialEq, Debug)]
+pub enum Ast {
+    Num(f64),
+    Operation(char, Box<Ast>, Box<Ast>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ParseErrorKind {
+    UnexpectedChar(char),
+    UnexpectedEnd,
+    UnclosedParenthesis,
+    UnmatchedParenthesis,
+    InvalidNumber(String),
+}
+
+// positions are in chars, counted from the start of the expression
+#[derive(PartialEq, Debug)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub position: usize,
+}
+
+pub struct Parser<'p> {
+    iter: Peekable<Chars<'p>>,
+    position: usize,
+}
+
+impl<'p> Parser<'p> {
+    pub fn new<'s>(src: &'p Cow<'s, str>) -> Self {
+        Parser { iter: src.chars().peekable(), position: 0 }
+    }
+
+    pub fn parse_expression(&mut self) -> Result<Ast, ParseError> {
+        let root = self.parse_sum()?;
+        match self.iter.peek().cloned() {
+            None => Ok(root),
+            Some(')') => Err(self.error(ParseErrorKind::UnmatchedParenthesis)),
+            Some(character) => Err(self.error(ParseErrorKind::UnexpectedChar(character)))
+        }
+    }
+
+    fn parse_sum(&mut self) -> Result<Ast, ParseError> {
+        let mut root = self.parse_term()?;
+        while let Some(operand) = self.low_priority_operand() {
+            root = Ast::Operation(operand, Box::new(root), Box::new(self.parse_term()?))
+        }
+        Ok(root)
+    }
+
+    fn low_priority_operand(&mut self) -> Option<char> {
+        match self.iter.peek() {
+            Some(&'+') | Some(&'-') => self.next(),
+            _ => None
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Ast, ParseError> {
+        let mut root = self.parse_num()?;
+        while let Some(operand) = self.high_priority_operand() {
+            root = Ast::Operation(operand, Box::new(root), Box::new(self.parse_num()?))
+        }
+        Ok(root)
+    }
+
+    fn high_priority_operand(&mut self) -> Option<char> {
+        let operand = match self.iter.peek() {
+            Some(&'×') | Some(&'*') => '×',
+            Some(&'÷') | Some(&'/') => '÷',
+            _ => return None
+        };
+        self.next();
+        Some(operand)
+    }
+
+    fn parse_num(&mut self) -> Result<Ast, ParseError> {
+        let start = self.position;
+        if self.iter.peek() == Some(&'(') {
+            self.next();
+            let sub_root = self.parse_sum()?;
+            return match self.next() {
+                Some(')') => Ok(sub_root),
+                _ => Err(ParseError { kind: ParseErrorKind::UnclosedParenthesis, position: start })
+            };
+        }
+        let mut num = String::new();
+        while let Some(character) = self.iter.peek().cloned() {
+            match character {
+                '-' if num.is_empty() => {}
+                digit if digit.is_ascii_digit() || digit == '.' => {}
+                _ => break
+            }
+            num.push(character);
+            self.next();
+        }
+        if num.is_empty() {
+            return Err(match self.iter.peek().cloned() {
+                None => self.error(ParseErrorKind::UnexpectedEnd),
+                Some(character) => self.error(ParseErrorKind::UnexpectedChar(character))
+            });
+        }
+        num.parse()
+            .map(Ast::Num)
+            .map_err(|_| ParseError { kind: ParseErrorKind::InvalidNumber(num), position: start })
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let next = self.iter.next();
+        if next.is_some() {
+            self.position += 1;
+        }
+        next
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError { kind, position: self.position }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(num: f64) -> Ast {
+        Ast::Num(num)
+    }
+
+    fn operation(operand: char, left: Ast, right: Ast) -> Ast {
+        Ast::Operation(operand, Box::new(left), Box::new(right))
+    }
+
+    #[test]
+    fn parse_negative_number() {
+        let expr = Cow::Borrowed("-4");
+        assert_eq!(Parser::new(&expr).parse_expression(), Ok(num(-4.0)));
+    }
+
+    #[test]
+    fn parse_addition() {
+        let expr = Cow::Borrowed("3+2");
+        assert_eq!(Parser::new(&expr).parse_expression(), Ok(operation('+', num(3.0), num(2.0))));
+    }
+
+    #[test]
+    fn parse_subtraction() {
+        let expr = Cow::Borrowed("3-1");
+        assert_eq!(Parser::new(&expr).parse_expression(), Ok(operation('-', num(3.0), num(1.0))));
+    }
+
+    #[test]
+    fn parse_multiplication() {
+        let expr = Cow::Borrowed("3×4");
+        assert_eq!(Parser::new(&expr).parse_expression(), Ok(operation('×', num(3.0), num(4.0))));
+    }
+
+    #[test]
+    fn parse_division() {
+        let expr = Cow::Borrowed("4÷2");
+        assert_eq!(Parser::new(&expr).parse_expression(), Ok(operation('÷', num(4.0), num(2.0))));
+    }
+
+    #[test]
+    fn parse_many_operations() {
+        let expr = Cow::Borrowed("3÷1+2×3÷3-1");
+        assert_eq!(
+            Parser::new(&expr).parse_expression(),
+            Ok(operation(
+                '-',
+                operation(
+                    '+',
+                    operation(
+                        '÷',
+                        num(3.0),
+                        num(1.0)
+                    ),
+                    operation(
+                        '÷',
+                        operation(
+                            '×',
+                            num(2.0),
+                            num(3.0)
+                        ),
+                        num(3.0)
+                    )
+                ),
+                num(1.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_expression_with_parenthesis() {
+        let expr = Cow::Borrowed("3÷(1+2)×(3-1)");
+        assert_eq!(
+            Parser::new(&expr).parse_expression(),
+            Ok(operation(
+                '×',
+                operation(
+                    '÷',
+                    num(3.0),
+                    operation(
+                        '+',
+                        num(1.0),
+                        num(2.0)
+                    )
+                ),
+                operation(
+                    '-',
+                    num(3.0),
+                    num(1.0)
+                )
+            ))
+        );
+    }
+
+    fn error(kind: ParseErrorKind, position: usize) -> Result<Ast, ParseError> {
+        Err(ParseError { kind, position })
+    }
+
+    #[test]
+    fn parse_ascii_operators() {
+        let expr = Cow::Borrowed("6*2/3");
+        assert_eq!(
+            Parser::new(&expr).parse_expression(),
+            Ok(operation('÷', operation('×', num(6.0), num(2.0)), num(3.0)))
+        );
+    }
+
+    #[test]
+    fn report_unmatched_parenthesis() {
+        let expr = Cow::Borrowed("(1+2))×3");
+        assert_eq!(Parser::new(&expr).parse_expression(), error(ParseErrorKind::UnmatchedParenthesis, 5));
+    }
+
+    #[test]
+    fn report_unclosed_parenthesis() {
+        let expr = Cow::Borrowed("2×(1+(2-3)");
+        assert_eq!(Parser::new(&expr).parse_expression(), error(ParseErrorKind::UnclosedParenthesis, 2));
+    }
+
+    #[test]
+    fn report_invalid_number() {
+        let expr = Cow::Borrowed("1+2.5.1");
+        assert_eq!(
+            Parser::new(&expr).parse_expression(),
+            error(ParseErrorKind::InvalidNumber("2.5.1".to_owned()), 2)
+        );
+    }
+
+    #[test]
+    fn report_missing_operand() {
+        let empty = Cow::Borrowed("");
+        let dangling = Cow::Borrowed("3+");
+        let unknown = Cow::Borrowed("3+x");
+        assert_eq!(Parser::new(&empty).parse_expression(), error(ParseErrorKind::UnexpectedEnd, 0));
+        assert_eq!(Parser::new(&dangling).parse_expression(), error(ParseErrorKind::UnexpectedEnd, 2));
+        assert_eq!(Parser::new(&unknown).parse_expression(), error(ParseErrorKind::UnexpectedChar('x'), 2));
+    }
+}
@@ -0,0 +1,221 @@
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::Chars;
+
+// longest first, so that `**` is not read as two `*`
+const OPERATORS: &[&str] = &["**", "+", "-", "*", "/", "%", "×", "÷"];
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum TokenKind {
+    // decimal text with `_` separators removed and hex already converted,
+    // ready for any `FromStr` number type
+    Num(String),
+    Ident(String),
+    Op(&'static str),
+    LeftParen,
+    RightParen,
+    Invalid(String),
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+pub struct Lexer<'s> {
+    src: &'s str,
+    iter: Peekable<Chars<'s>>,
+    start: usize,
+    offset: usize,
+    position: usize,
+}
+
+impl<'s> Lexer<'s> {
+    pub fn new(src: &'s str) -> Self {
+        Lexer { src, iter: src.chars().peekable(), start: 0, offset: 0, position: 0 }
+    }
+
+    fn lex_number(&mut self) -> TokenKind {
+        let mut text = String::new();
+        let radix = if self.rest().starts_with("0x") || self.rest().starts_with("0X") {
+            self.skip();
+            self.skip();
+            16
+        } else {
+            10
+        };
+        while let Some(character) = self.take_ahead() {
+            match character {
+                '_' => {}
+                'e' | 'E' if radix == 10 => {
+                    text.push(character);
+                    self.skip();
+                    if let Some(sign @ '+') | Some(sign @ '-') = self.take_ahead() {
+                        text.push(sign);
+                    } else {
+                        continue;
+                    }
+                }
+                character if character.is_alphanumeric() || character == '.' => text.push(character),
+                _ => break
+            }
+            self.skip();
+        }
+        let num = if radix == 16 {
+            u64::from_str_radix(&text, 16).ok().map(|num| num.to_string())
+        } else {
+            text.parse::<f64>().ok().map(|_| text.clone())
+        };
+        match num {
+            Some(num) => TokenKind::Num(num),
+            _ => TokenKind::Invalid(self.src[self.start..self.offset].to_owned())
+        }
+    }
+
+    fn lex_ident(&mut self) -> TokenKind {
+        let mut ident = String::new();
+        while let Some(character) = self.take_ahead() {
+            if !character.is_alphanumeric() && character != '_' {
+                break;
+            }
+            ident.push(character);
+            self.skip();
+        }
+        TokenKind::Ident(ident)
+    }
+
+    fn lex_symbol(&mut self, character: char) -> TokenKind {
+        let op = OPERATORS.iter().find(|op| self.rest().starts_with(**op)).cloned();
+        match op {
+            Some(op) => {
+                for _ in op.chars() {
+                    self.skip();
+                }
+                TokenKind::Op(op)
+            }
+            None => {
+                self.skip();
+                match character {
+                    '(' => TokenKind::LeftParen,
+                    ')' => TokenKind::RightParen,
+                    character => TokenKind::Invalid(character.to_string())
+                }
+            }
+        }
+    }
+
+    fn rest(&self) -> &'s str {
+        &self.src[self.offset..]
+    }
+
+    fn take_ahead(&mut self) -> Option<char> {
+        self.iter.peek().cloned()
+    }
+
+    fn skip(&mut self) {
+        if let Some(character) = self.iter.next() {
+            self.offset += character.len_utf8();
+            self.position += 1;
+        }
+    }
+}
+
+impl<'s> Iterator for Lexer<'s> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        while self.take_ahead().is_some_and(char::is_whitespace) {
+            self.skip();
+        }
+        let character = self.take_ahead()?;
+        let start = self.position;
+        self.start = self.offset;
+        let kind = if character.is_ascii_digit() || character == '.' {
+            self.lex_number()
+        } else if character.is_alphabetic() || character == '_' {
+            self.lex_ident()
+        } else {
+            self.lex_symbol(character)
+        };
+        Some(Token { kind, span: start..self.position })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(src: &str) -> Vec<TokenKind> {
+        Lexer::new(src).map(|token| token.kind).collect()
+    }
+
+    fn num(num: &str) -> TokenKind {
+        TokenKind::Num(num.to_owned())
+    }
+
+    #[test]
+    fn operators_and_parenthesis() {
+        assert_eq!(
+            kinds("(1+2) × 3÷4"),
+            vec![
+                TokenKind::LeftParen,
+                num("1"),
+                TokenKind::Op("+"),
+                num("2"),
+                TokenKind::RightParen,
+                TokenKind::Op("×"),
+                num("3"),
+                TokenKind::Op("÷"),
+                num("4")
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_char_operators() {
+        assert_eq!(kinds("2**3*4"), vec![num("2"), TokenKind::Op("**"), num("3"), TokenKind::Op("*"), num("4")]);
+    }
+
+    #[test]
+    fn number_literals() {
+        assert_eq!(
+            kinds("1e-3 2.5E+2 .5 0x1F 1_000 3e2"),
+            vec![num("1e-3"), num("2.5E+2"), num(".5"), num("31"), num("1000"), num("3e2")]
+        );
+    }
+
+    #[test]
+    fn subtraction_after_exponent_needs_digits() {
+        assert_eq!(kinds("1e-x"), vec![TokenKind::Invalid("1e-x".to_owned())]);
+        assert_eq!(kinds("1-e"), vec![num("1"), TokenKind::Op("-"), TokenKind::Ident("e".to_owned())]);
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(
+            kinds("rate_2 * _x"),
+            vec![TokenKind::Ident("rate_2".to_owned()), TokenKind::Op("*"), TokenKind::Ident("_x".to_owned())]
+        );
+    }
+
+    #[test]
+    fn malformed_tokens() {
+        assert_eq!(
+            kinds("1.2.3 0xZ 2x &"),
+            vec![
+                TokenKind::Invalid("1.2.3".to_owned()),
+                TokenKind::Invalid("0xZ".to_owned()),
+                TokenKind::Invalid("2x".to_owned()),
+                TokenKind::Invalid("&".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn spans_count_chars() {
+        let spans: Vec<Range<usize>> = Lexer::new(" 12 ×  x1").map(|token| token.span).collect();
+
+        assert_eq!(spans, vec![1..3, 4..5, 7..9]);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod lexer;
+
 pub mod day_1;
 pub mod day_2;
 pub mod day_3;
@@ -12,3 +14,6 @@ pub mod day_11;
 pub mod day_12;
 pub mod day_13;
 pub mod day_14;
+pub mod day_15;
+pub mod day_16;
+pub mod day_17;
@@ -0,0 +1,165 @@
+use algebraic_expression_parser_kata::lexer::{Lexer, Token, TokenKind};
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Neg, Range, Sub};
+use std::str::FromStr;
+
+#[derive(PartialEq, Debug)]
+pub enum CalcError<E> {
+    Num(E, Range<usize>),
+    Unexpected(Token),
+    UnexpectedEnd,
+}
+
+pub fn calculate<'s, N>(src: Cow<'s, str>) -> Result<N, CalcError<N::Err>>
+    where N: Add<Output=N> + Sub<Output=N> + Mul<Output=N> + Div<Output=N> + Neg<Output=N> + FromStr {
+    let mut eval = Evaluator::new(Lexer::new(&src).peekable());
+    let ret = eval.parse_expression()?;
+    match eval.tokens.next() {
+        Some(token) => Err(CalcError::Unexpected(token)),
+        None => Ok(ret)
+    }
+}
+
+struct Evaluator<'s, N> {
+    tokens: Peekable<Lexer<'s>>,
+    phantom: PhantomData<N>,
+}
+
+impl<'s, N> Evaluator<'s, N>
+    where N: Add<Output=N> + Sub<Output=N> + Mul<Output=N> + Div<Output=N> + Neg<Output=N> + FromStr {
+    fn new(tokens: Peekable<Lexer<'s>>) -> Self {
+        Evaluator { tokens, phantom: PhantomData }
+    }
+
+    fn parse_expression(&mut self) -> Result<N, CalcError<N::Err>> {
+        let mut ret = self.parse_term()?;
+        while let Some(operation) = self.resolve_low_priority_operation() {
+            self.skip();
+            ret = operation(ret, self.parse_term()?);
+        }
+        Ok(ret)
+    }
+
+    fn resolve_low_priority_operation(&mut self) -> Option<fn(N, N) -> N> {
+        match self.take_ahead() {
+            Some(TokenKind::Op("+")) => Some(add),
+            Some(TokenKind::Op("-")) => Some(sub),
+            _ => None
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<N, CalcError<N::Err>> {
+        let mut ret = self.parse_factor()?;
+        while let Some(operation) = self.resolve_high_priority_operation() {
+            self.skip();
+            ret = operation(ret, self.parse_factor()?);
+        }
+        Ok(ret)
+    }
+
+    fn resolve_high_priority_operation(&mut self) -> Option<fn(N, N) -> N> {
+        match self.take_ahead() {
+            Some(TokenKind::Op("*")) | Some(TokenKind::Op("×")) => Some(mul),
+            Some(TokenKind::Op("/")) | Some(TokenKind::Op("÷")) => Some(div),
+            _ => None
+        }
+    }
+
+    fn parse_factor(&mut self) -> Result<N, CalcError<N::Err>> {
+        match self.tokens.next() {
+            Some(Token { kind: TokenKind::Op("-"), .. }) => self.parse_factor().map(|num| -num),
+            Some(Token { kind: TokenKind::Num(num), span }) => num.parse().map_err(|e| CalcError::Num(e, span)),
+            Some(Token { kind: TokenKind::LeftParen, .. }) => {
+                let ret = self.parse_expression()?;
+                match self.tokens.next() {
+                    Some(Token { kind: TokenKind::RightParen, .. }) => Ok(ret),
+                    Some(token) => Err(CalcError::Unexpected(token)),
+                    None => Err(CalcError::UnexpectedEnd)
+                }
+            }
+            Some(token) => Err(CalcError::Unexpected(token)),
+            None => Err(CalcError::UnexpectedEnd)
+        }
+    }
+
+    fn take_ahead(&mut self) -> Option<TokenKind> {
+        self.tokens.peek().map(|token| token.kind.clone())
+    }
+
+    fn skip(&mut self) {
+        self.tokens.next();
+    }
+}
+
+fn add<N: Add<Output=N>>(acc: N, num: N) -> N {
+    acc + num
+}
+
+fn sub<N: Sub<Output=N>>(acc: N, num: N) -> N {
+    acc - num
+}
+
+fn mul<N: Mul<Output=N>>(acc: N, num: N) -> N {
+    acc * num
+}
+
+fn div<N: Div<Output=N>>(acc: N, num: N) -> N {
+    acc / num
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_negative_number() {
+        assert_eq!(calculate(Cow::Borrowed("-8")), Ok(-8.0));
+    }
+
+    #[test]
+    fn evaluate_many_operations() {
+        assert_eq!(calculate(Cow::Borrowed("3 + 2×4 - 81÷3")), Ok(-16.0));
+    }
+
+    #[test]
+    fn evaluate_operations_with_parenthesis() {
+        assert_eq!(calculate(Cow::Borrowed("((3 + 2 * 4) - 81) / 7")), Ok(-10.0));
+    }
+
+    #[test]
+    fn evaluate_negated_parenthesis() {
+        assert_eq!(calculate(Cow::Borrowed("2 × -(1 - 4)")), Ok(6));
+    }
+
+    #[test]
+    fn evaluate_scientific_notation() {
+        assert_eq!(calculate(Cow::Borrowed("1e-3 × 2.5e3")), Ok(2.5));
+    }
+
+    #[test]
+    fn evaluate_hex_and_separated_numbers() {
+        assert_eq!(calculate(Cow::Borrowed("0x1F + 1_000")), Ok(1031));
+    }
+
+    #[test]
+    fn number_that_does_not_fit_the_type() {
+        assert_eq!(
+            calculate::<i32>(Cow::Borrowed("1 + 2.5")),
+            Err(CalcError::Num("2.5".parse::<i32>().unwrap_err(), 4..7))
+        );
+    }
+
+    #[test]
+    fn unexpected_token() {
+        assert_eq!(
+            calculate::<f64>(Cow::Borrowed("2 ** 3")),
+            Err(CalcError::Unexpected(Token { kind: TokenKind::Op("**"), span: 2..4 }))
+        );
+        assert_eq!(
+            calculate::<f64>(Cow::Borrowed("(1 + 2")),
+            Err(CalcError::UnexpectedEnd)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+use std::fmt;
+
+#[derive(PartialEq, Debug)]
+pub enum AddError {
+    InvalidHeader(String),
+    InvalidNumber(String),
+    Negatives(Vec<i64>),
+}
+
+impl fmt::Display for AddError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AddError::InvalidHeader(ref header) => write!(f, "invalid delimiter header `{}`", header),
+            AddError::InvalidNumber(ref num) => write!(f, "invalid number `{}`", num),
+            AddError::Negatives(ref negatives) => {
+                let negatives: Vec<String> = negatives.iter().map(|num| num.to_string()).collect();
+                write!(f, "negatives not allowed: {}", negatives.join(", "))
+            }
+        }
+    }
+}
+
+pub fn add(src: &str) -> Result<i64, AddError> {
+    if src.is_empty() {
+        return Ok(0);
+    }
+    let (mut delimiters, numbers) = parse_header(src)?;
+    delimiters.push(",".to_owned());
+    delimiters.push("\n".to_owned());
+    // the longest delimiter wins, so `**` is not taken for two `*`
+    delimiters.sort_by_key(|delimiter| Reverse(delimiter.len()));
+    let mut sum = 0;
+    let mut negatives = vec![];
+    for num in split(numbers, &delimiters) {
+        let num: i64 = num.parse().map_err(|_| AddError::InvalidNumber(num.to_owned()))?;
+        if num < 0 {
+            negatives.push(num);
+        } else if num <= 1000 {
+            sum += num;
+        }
+    }
+    if negatives.is_empty() {
+        Ok(sum)
+    } else {
+        Err(AddError::Negatives(negatives))
+    }
+}
+
+fn parse_header(src: &str) -> Result<(Vec<String>, &str), AddError> {
+    if !src.starts_with("//") {
+        return Ok((vec![], src));
+    }
+    let end = src.find('\n').ok_or_else(|| AddError::InvalidHeader(src.to_owned()))?;
+    let header = &src[2..end];
+    let invalid = || AddError::InvalidHeader(header.to_owned());
+    let mut delimiters = vec![];
+    if header.starts_with('[') {
+        let mut rest = header;
+        while !rest.is_empty() {
+            if !rest.starts_with('[') {
+                return Err(invalid());
+            }
+            let close = rest.find(']').ok_or_else(invalid)?;
+            if close == 1 {
+                return Err(invalid());
+            }
+            delimiters.push(rest[1..close].to_owned());
+            rest = &rest[close + 1..];
+        }
+    } else if header.chars().count() == 1 {
+        delimiters.push(header.to_owned());
+    } else {
+        return Err(invalid());
+    }
+    Ok((delimiters, &src[end + 1..]))
+}
+
+fn split<'s>(src: &'s str, delimiters: &[String]) -> Vec<&'s str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut index = 0;
+    while index < src.len() {
+        match delimiters.iter().find(|delimiter| src[index..].starts_with(delimiter.as_str())) {
+            Some(delimiter) => {
+                parts.push(&src[start..index]);
+                index += delimiter.len();
+                start = index;
+            }
+            None => index += src[index..].chars().next().map_or(1, char::len_utf8)
+        }
+    }
+    parts.push(&src[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_string() {
+        assert_eq!(add(""), Ok(0));
+    }
+
+    #[test]
+    fn one_and_two_numbers() {
+        assert_eq!(add("1"), Ok(1));
+        assert_eq!(add("1,2"), Ok(3));
+    }
+
+    #[test]
+    fn any_amount_of_numbers() {
+        assert_eq!(add("1,2,3,4,5,6,7,8,9"), Ok(45));
+    }
+
+    #[test]
+    fn new_line_delimiter() {
+        assert_eq!(add("1\n2,3"), Ok(6));
+    }
+
+    #[test]
+    fn custom_delimiter() {
+        assert_eq!(add("//;\n1;2"), Ok(3));
+        assert_eq!(add("//;\n1;2\n3,4"), Ok(10));
+    }
+
+    #[test]
+    fn multi_char_delimiter() {
+        assert_eq!(add("//[***]\n1***2***3"), Ok(6));
+    }
+
+    #[test]
+    fn many_delimiters() {
+        assert_eq!(add("//[*][%]\n1*2%3"), Ok(6));
+        assert_eq!(add("//[**][*]\n1**2*3"), Ok(6));
+        assert_eq!(add("//[×][÷÷]\n1×2÷÷3"), Ok(6));
+    }
+
+    #[test]
+    fn numbers_bigger_than_thousand_are_ignored() {
+        assert_eq!(add("2,1001,1000"), Ok(1002));
+    }
+
+    #[test]
+    fn negatives_are_all_listed() {
+        let error = add("1,-2,3\n-4").unwrap_err();
+
+        assert_eq!(error, AddError::Negatives(vec![-2, -4]));
+        assert_eq!(error.to_string(), "negatives not allowed: -2, -4");
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(add("1,\n2"), Err(AddError::InvalidNumber("".to_owned())));
+        assert_eq!(add("1;2"), Err(AddError::InvalidNumber("1;2".to_owned())));
+        assert_eq!(add("//;;\n1;;2"), Err(AddError::InvalidHeader(";;".to_owned())));
+        assert_eq!(add("//[]\n1"), Err(AddError::InvalidHeader("[]".to_owned())));
+        assert_eq!(add("//[*\n1*2"), Err(AddError::InvalidHeader("[*".to_owned())));
+        assert_eq!(add("//;1;2"), Err(AddError::InvalidHeader("//;1;2".to_owned())));
+    }
+}
@@ -0,0 +1,275 @@
+use std::io::{self, BufReader, Read};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::{self, FromStr};
+
+#[derive(Debug)]
+pub enum StreamError<E> {
+    Io(io::Error),
+    InvalidUtf8(usize),
+    Num(E, usize),
+    Unexpected(char, usize),
+    UnexpectedEnd(usize),
+}
+
+impl<E> StreamError<E> {
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            StreamError::Io(_) => None,
+            StreamError::InvalidUtf8(offset)
+            | StreamError::Num(_, offset)
+            | StreamError::Unexpected(_, offset)
+            | StreamError::UnexpectedEnd(offset) => Some(offset),
+        }
+    }
+}
+
+pub fn calculate_chars<I, N>(chars: I) -> Result<N, StreamError<N::Err>>
+    where I: IntoIterator<Item=char>,
+          N: Add<Output=N> + Sub<Output=N> + Mul<Output=N> + Div<Output=N> + Neg<Output=N> + FromStr {
+    let mut eval = Evaluator::new();
+    let mut offset = 0;
+    for c in chars {
+        eval.feed(c, offset)?;
+        offset += c.len_utf8();
+    }
+    eval.finish(offset)
+}
+
+pub fn calculate_read<R, N>(input: R) -> Result<N, StreamError<N::Err>>
+    where R: Read,
+          N: Add<Output=N> + Sub<Output=N> + Mul<Output=N> + Div<Output=N> + Neg<Output=N> + FromStr {
+    let mut eval = Evaluator::new();
+    let mut bytes = BufReader::new(input).bytes();
+    let mut offset = 0;
+    let mut buf = [0; 4];
+    while let Some(byte) = bytes.next() {
+        buf[0] = byte.map_err(StreamError::Io)?;
+        let width = match buf[0] {
+            0x00..=0x7F => 1,
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return Err(StreamError::InvalidUtf8(offset))
+        };
+        for slot in buf.iter_mut().take(width).skip(1) {
+            match bytes.next() {
+                Some(byte) => *slot = byte.map_err(StreamError::Io)?,
+                None => return Err(StreamError::InvalidUtf8(offset))
+            }
+        }
+        let c = str::from_utf8(&buf[..width])
+            .map_err(|_| StreamError::InvalidUtf8(offset))?
+            .chars()
+            .next()
+            .unwrap();
+        eval.feed(c, offset)?;
+        offset += width;
+    }
+    eval.finish(offset)
+}
+
+type Operation<N> = fn(N, N) -> N;
+
+// one frame per open parenthesis, so a flat expression of any length
+// is evaluated with a single frame and the digits of the current number
+struct Frame<N> {
+    sum: Option<(N, Operation<N>)>,
+    product: Option<(N, Operation<N>)>,
+    value: Option<N>,
+    negative: bool,
+}
+
+impl<N> Frame<N> {
+    fn new() -> Self {
+        Frame { sum: None, product: None, value: None, negative: false }
+    }
+}
+
+struct Evaluator<N> {
+    frames: Vec<Frame<N>>,
+    num: String,
+    num_start: usize,
+}
+
+impl<N> Evaluator<N>
+    where N: Add<Output=N> + Sub<Output=N> + Mul<Output=N> + Div<Output=N> + Neg<Output=N> + FromStr {
+    fn new() -> Self {
+        Evaluator { frames: vec![Frame::new()], num: String::new(), num_start: 0 }
+    }
+
+    fn feed(&mut self, c: char, offset: usize) -> Result<(), StreamError<N::Err>> {
+        if c.is_ascii_digit() || c == '.' {
+            if self.num.is_empty() {
+                if self.frame().value.is_some() {
+                    return Err(StreamError::Unexpected(c, offset));
+                }
+                self.num_start = offset;
+            }
+            self.num.push(c);
+            return Ok(());
+        }
+        self.end_num()?;
+        let expect_operand = self.frame().value.is_none();
+        match c {
+            c if c.is_whitespace() => {}
+            '-' if expect_operand => self.frame().negative ^= true,
+            '(' if expect_operand => self.frames.push(Frame::new()),
+            '+' if !expect_operand => self.low(add),
+            '-' if !expect_operand => self.low(sub),
+            '×' | '*' if !expect_operand => self.high(mul),
+            '÷' | '/' if !expect_operand => self.high(div),
+            ')' if !expect_operand && self.frames.len() > 1 => {
+                let value = self.close();
+                self.operand(value);
+            }
+            c => return Err(StreamError::Unexpected(c, offset))
+        }
+        Ok(())
+    }
+
+    fn finish(mut self, offset: usize) -> Result<N, StreamError<N::Err>> {
+        self.end_num()?;
+        if self.frames.len() > 1 || self.frame().value.is_none() {
+            return Err(StreamError::UnexpectedEnd(offset));
+        }
+        Ok(self.close())
+    }
+
+    fn end_num(&mut self) -> Result<(), StreamError<N::Err>> {
+        if self.num.is_empty() {
+            return Ok(());
+        }
+        let num = self.num.parse().map_err(|e| StreamError::Num(e, self.num_start))?;
+        self.num.clear();
+        self.operand(num);
+        Ok(())
+    }
+
+    fn operand(&mut self, value: N) {
+        let frame = self.frame();
+        let value = if frame.negative { -value } else { value };
+        frame.negative = false;
+        frame.value = Some(match frame.product.take() {
+            Some((product, operation)) => operation(product, value),
+            None => value
+        });
+    }
+
+    fn high(&mut self, operation: Operation<N>) {
+        let frame = self.frame();
+        frame.product = frame.value.take().map(|value| (value, operation));
+    }
+
+    fn low(&mut self, operation: Operation<N>) {
+        let frame = self.frame();
+        let value = frame.value.take().unwrap();
+        frame.sum = Some(match frame.sum.take() {
+            Some((sum, pending)) => (pending(sum, value), operation),
+            None => (value, operation)
+        });
+    }
+
+    fn close(&mut self) -> N {
+        let mut frame = self.frames.pop().unwrap();
+        let value = frame.value.take().unwrap();
+        match frame.sum.take() {
+            Some((sum, operation)) => operation(sum, value),
+            None => value
+        }
+    }
+
+    fn frame(&mut self) -> &mut Frame<N> {
+        self.frames.last_mut().unwrap()
+    }
+}
+
+fn add<N: Add<Output=N>>(acc: N, num: N) -> N {
+    acc + num
+}
+
+fn sub<N: Sub<Output=N>>(acc: N, num: N) -> N {
+    acc - num
+}
+
+fn mul<N: Mul<Output=N>>(acc: N, num: N) -> N {
+    acc * num
+}
+
+fn div<N: Div<Output=N>>(acc: N, num: N) -> N {
+    acc / num
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "1+1+...+1" produced on the fly, never held in memory as a whole
+    struct Ones {
+        remaining: usize,
+        next_plus: bool,
+    }
+
+    impl Read for Ones {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut written = 0;
+            while written < buf.len() && self.remaining > 0 {
+                if self.next_plus {
+                    buf[written] = b'+';
+                } else {
+                    buf[written] = b'1';
+                    self.remaining -= 1;
+                }
+                self.next_plus = !self.next_plus && self.remaining > 0;
+                written += 1;
+            }
+            Ok(written)
+        }
+    }
+
+    #[test]
+    fn evaluate_chars() {
+        assert_eq!(calculate_chars::<_, f64>("3 + 2×4 - 81÷3".chars()).unwrap(), -16.0);
+    }
+
+    #[test]
+    fn evaluate_parenthesis_and_negation() {
+        assert_eq!(calculate_chars::<_, i64>("-((3 + 2*4) - 81) / 7".chars()).unwrap(), 10);
+        assert_eq!(calculate_chars::<_, i64>("2 × -(1 - 4)".chars()).unwrap(), 6);
+    }
+
+    #[test]
+    fn evaluate_reader() {
+        assert_eq!(calculate_read::<_, f64>("1.5 × 4 ÷ (2 + 1)".as_bytes()).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn evaluate_long_generated_sum() {
+        let ones = Ones { remaining: 1_000_000, next_plus: false };
+
+        assert_eq!(calculate_read::<_, i64>(ones).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn errors_report_byte_offsets() {
+        assert_eq!(calculate_read::<_, i64>("1 × 2 ÷ 3 + x".as_bytes()).unwrap_err().offset(), Some(14));
+        assert_eq!(calculate_chars::<_, i64>("1 + 2 3".chars()).unwrap_err().offset(), Some(6));
+        assert_eq!(calculate_chars::<_, i64>("(1 + 2".chars()).unwrap_err().offset(), Some(6));
+        assert_eq!(calculate_chars::<_, i64>("1 + )".chars()).unwrap_err().offset(), Some(4));
+    }
+
+    #[test]
+    fn number_errors_point_at_the_number() {
+        match calculate_chars::<_, i64>("10 + 2.5".chars()) {
+            Err(StreamError::Num(_, offset)) => assert_eq!(offset, 5),
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        match calculate_read::<_, i64>(&[b'1', b'+', 0xFF][..]) {
+            Err(StreamError::InvalidUtf8(offset)) => assert_eq!(offset, 2),
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use algebraic_expression_parser_kata::lexer::{Lexer, Token, TokenKind};
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::num::{IntErrorKind, ParseIntError};
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(PartialEq, Debug)]
+pub enum CheckedError {
+    DivisionByZero,
+    Overflow,
+    Parse(Range<usize>),
+}
+
+pub trait Integer: Copy + PartialEq + FromStr<Err=ParseIntError> {
+    const ZERO: Self;
+
+    fn add(self, other: Self) -> Option<Self>;
+    fn sub(self, other: Self) -> Option<Self>;
+    fn mul(self, other: Self) -> Option<Self>;
+    fn div(self, other: Self) -> Option<Self>;
+    fn rem(self, other: Self) -> Option<Self>;
+    fn neg(self) -> Option<Self>;
+}
+
+macro_rules! integer {
+    ($($num:ty),*) => {
+        $(
+            impl Integer for $num {
+                const ZERO: Self = 0;
+
+                fn add(self, other: Self) -> Option<Self> { self.checked_add(other) }
+                fn sub(self, other: Self) -> Option<Self> { self.checked_sub(other) }
+                fn mul(self, other: Self) -> Option<Self> { self.checked_mul(other) }
+                fn div(self, other: Self) -> Option<Self> { self.checked_div(other) }
+                fn rem(self, other: Self) -> Option<Self> { self.checked_rem(other) }
+                fn neg(self) -> Option<Self> { self.checked_neg() }
+            }
+        )*
+    }
+}
+
+integer!(i64, i128);
+
+type Operation<N> = fn(N, N) -> Result<N, CheckedError>;
+
+// division truncates toward zero and the remainder takes the sign of the
+// dividend, so `a == a / b * b + a % b` always holds
+pub fn calculate<'s, N: Integer>(src: Cow<'s, str>) -> Result<N, CheckedError> {
+    let end = src.chars().count();
+    let mut eval = Evaluator::new(Lexer::new(&src).peekable(), end);
+    let ret = eval.parse_expression()?;
+    match eval.tokens.next() {
+        Some(token) => Err(CheckedError::Parse(token.span)),
+        None => Ok(ret)
+    }
+}
+
+struct Evaluator<'s, N> {
+    tokens: Peekable<Lexer<'s>>,
+    end: usize,
+    phantom: PhantomData<N>,
+}
+
+impl<'s, N: Integer> Evaluator<'s, N> {
+    fn new(tokens: Peekable<Lexer<'s>>, end: usize) -> Self {
+        Evaluator { tokens, end, phantom: PhantomData }
+    }
+
+    fn parse_expression(&mut self) -> Result<N, CheckedError> {
+        let mut ret = self.parse_term()?;
+        while let Some(operation) = self.resolve_low_priority_operation() {
+            self.skip();
+            ret = operation(ret, self.parse_term()?)?;
+        }
+        Ok(ret)
+    }
+
+    fn resolve_low_priority_operation(&mut self) -> Option<Operation<N>> {
+        match self.take_ahead() {
+            Some(TokenKind::Op("+")) => Some(add),
+            Some(TokenKind::Op("-")) => Some(sub),
+            _ => None
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<N, CheckedError> {
+        let mut ret = self.parse_factor()?;
+        while let Some(operation) = self.resolve_high_priority_operation() {
+            self.skip();
+            ret = operation(ret, self.parse_factor()?)?;
+        }
+        Ok(ret)
+    }
+
+    fn resolve_high_priority_operation(&mut self) -> Option<Operation<N>> {
+        match self.take_ahead() {
+            Some(TokenKind::Op("*")) | Some(TokenKind::Op("×")) => Some(mul),
+            Some(TokenKind::Op("/")) | Some(TokenKind::Op("÷")) => Some(div),
+            Some(TokenKind::Op("%")) => Some(rem),
+            _ => None
+        }
+    }
+
+    fn parse_factor(&mut self) -> Result<N, CheckedError> {
+        match self.tokens.next() {
+            Some(Token { kind: TokenKind::Op("-"), .. }) => {
+                // `-` in front of a literal is part of it, so that the minimum value parses
+                if let Some(Token { kind: TokenKind::Num(num), span }) = self.tokens.peek().cloned() {
+                    self.skip();
+                    return parse_num(&format!("-{}", num), span);
+                }
+                self.parse_factor()?.neg().ok_or(CheckedError::Overflow)
+            }
+            Some(Token { kind: TokenKind::Num(num), span }) => parse_num(&num, span),
+            Some(Token { kind: TokenKind::LeftParen, .. }) => {
+                let ret = self.parse_expression()?;
+                match self.tokens.next() {
+                    Some(Token { kind: TokenKind::RightParen, .. }) => Ok(ret),
+                    Some(token) => Err(CheckedError::Parse(token.span)),
+                    None => Err(CheckedError::Parse(self.end..self.end))
+                }
+            }
+            Some(token) => Err(CheckedError::Parse(token.span)),
+            None => Err(CheckedError::Parse(self.end..self.end))
+        }
+    }
+
+    fn take_ahead(&mut self) -> Option<TokenKind> {
+        self.tokens.peek().map(|token| token.kind.clone())
+    }
+
+    fn skip(&mut self) {
+        self.tokens.next();
+    }
+}
+
+fn add<N: Integer>(acc: N, num: N) -> Result<N, CheckedError> {
+    acc.add(num).ok_or(CheckedError::Overflow)
+}
+
+fn sub<N: Integer>(acc: N, num: N) -> Result<N, CheckedError> {
+    acc.sub(num).ok_or(CheckedError::Overflow)
+}
+
+fn mul<N: Integer>(acc: N, num: N) -> Result<N, CheckedError> {
+    acc.mul(num).ok_or(CheckedError::Overflow)
+}
+
+fn div<N: Integer>(acc: N, num: N) -> Result<N, CheckedError> {
+    if num == N::ZERO {
+        return Err(CheckedError::DivisionByZero);
+    }
+    acc.div(num).ok_or(CheckedError::Overflow)
+}
+
+fn rem<N: Integer>(acc: N, num: N) -> Result<N, CheckedError> {
+    if num == N::ZERO {
+        return Err(CheckedError::DivisionByZero);
+    }
+    acc.rem(num).ok_or(CheckedError::Overflow)
+}
+
+fn parse_num<N: Integer>(num: &str, span: Range<usize>) -> Result<N, CheckedError> {
+    num.parse().map_err(|e: ParseIntError| match *e.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => CheckedError::Overflow,
+        _ => CheckedError::Parse(span)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_many_operations() {
+        assert_eq!(calculate::<i64>(Cow::Borrowed("3 + 2×4 - 81÷3")), Ok(-16));
+    }
+
+    #[test]
+    fn integer_division_and_remainder() {
+        assert_eq!(calculate::<i64>(Cow::Borrowed("7 ÷ 2")), Ok(3));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("-7 / 2")), Ok(-3));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("-7 % 2")), Ok(-1));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("-7 / 2 * 2 + -7 % 2")), Ok(-7));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(calculate::<i64>(Cow::Borrowed("1÷0")), Err(CheckedError::DivisionByZero));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("1 % (2 - 2)")), Err(CheckedError::DivisionByZero));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(calculate::<i64>(Cow::Borrowed("9223372036854775807 + 1")), Err(CheckedError::Overflow));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("-9223372036854775808 / -1")), Err(CheckedError::Overflow));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("-(-9223372036854775808)")), Err(CheckedError::Overflow));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("9223372036854775808")), Err(CheckedError::Overflow));
+    }
+
+    #[test]
+    fn minimum_value_literal() {
+        assert_eq!(calculate::<i64>(Cow::Borrowed("-9223372036854775808")), Ok(i64::MIN));
+    }
+
+    #[test]
+    fn wider_integers() {
+        assert_eq!(
+            calculate::<i128>(Cow::Borrowed("9223372036854775807 * 4")),
+            Ok(36_893_488_147_419_103_228)
+        );
+    }
+
+    #[test]
+    fn parse_errors_carry_spans() {
+        assert_eq!(calculate::<i64>(Cow::Borrowed("1 + 2.5")), Err(CheckedError::Parse(4..7)));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("(1 + 2")), Err(CheckedError::Parse(6..6)));
+        assert_eq!(calculate::<i64>(Cow::Borrowed("1 + x")), Err(CheckedError::Parse(4..5)));
+    }
+}
@@ -1,3 +1,5 @@
+extern crate algebraic_expression_parser_kata;
+
 pub mod day_1;
 pub mod day_2;
 pub mod day_3;
@@ -12,3 +14,7 @@ pub mod day_11;
 pub mod day_12;
 pub mod day_13;
 pub mod day_14;
+pub mod day_15;
+pub mod day_16;
+pub mod day_17;
+pub mod day_18;
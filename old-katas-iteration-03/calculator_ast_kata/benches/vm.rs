@@ -0,0 +1,56 @@
+use calculator_ast_kata::{Ast, Vm};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const FORMULA: &str = "3*x^2 + 2*x*y - sin(y)/4 + sqrt(x) - exp(y/x)";
+const ROWS: usize = 1_000_000;
+
+fn dataset() -> Vec<(f64, f64)> {
+    (0..ROWS)
+        .map(|row| (1.0 + (row % 97) as f64, (row % 13) as f64 - 6.0))
+        .collect()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{:<12} {:>10.2?} total {:>8.1} ns/row",
+        name,
+        elapsed,
+        elapsed.as_nanos() as f64 / ROWS as f64
+    );
+}
+
+fn tree_walking(ast: &Ast, rows: &[(f64, f64)]) -> Duration {
+    let mut vars: HashMap<String, f64> = vec![("x".to_owned(), 0.0), ("y".to_owned(), 0.0)]
+        .into_iter()
+        .collect();
+    let start = Instant::now();
+    for &(x, y) in rows {
+        *vars.get_mut("x").unwrap() = x;
+        *vars.get_mut("y").unwrap() = y;
+        black_box(ast.eval(black_box(&vars)).unwrap());
+    }
+    start.elapsed()
+}
+
+fn bytecode(ast: &Ast, rows: &[(f64, f64)]) -> Duration {
+    let program = ast.compile().unwrap();
+    let mut vm = Vm::default();
+    let start = Instant::now();
+    for &(x, y) in rows {
+        black_box(vm.run(&program, black_box(&[x, y])).unwrap());
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let ast: Ast = FORMULA.parse().unwrap();
+    let rows = dataset();
+
+    println!("{} over {} rows", FORMULA, ROWS);
+    report("ast eval", tree_walking(&ast, &rows));
+    report("ast simplify", tree_walking(&ast.simplify(), &rows));
+    report("bytecode vm", bytecode(&ast, &rows));
+    report("vm simplify", bytecode(&ast.simplify(), &rows));
+}
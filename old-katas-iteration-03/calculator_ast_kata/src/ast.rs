@@ -0,0 +1,1333 @@
+use crate::number::Number;
+use crate::unit::Unit;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::iter::{Enumerate, Peekable};
+use std::marker::PhantomData;
+use std::str::{Chars, FromStr};
+
+type Cursor<'c> = Peekable<Enumerate<Chars<'c>>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ast<N = f64> {
+    Num(N),
+    Var(String),
+    Neg(Box<Ast<N>>),
+    Op(char, Box<Ast<N>>, Box<Ast<N>>),
+    Call(Func, Box<Ast<N>>),
+    Bool(bool),
+    Not(Box<Ast<N>>),
+    Cmp(Comparison, Box<Ast<N>>, Box<Ast<N>>),
+    And(Box<Ast<N>>, Box<Ast<N>>),
+    Or(Box<Ast<N>>, Box<Ast<N>>),
+    If(Box<Ast<N>>, Box<Ast<N>>, Box<Ast<N>>),
+    Convert(Box<Ast<N>>, Unit),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    pub fn test(self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (Comparison::Ne, None) => true,
+            (_, None) => false,
+            (Comparison::Eq, Some(ordering)) => ordering == Ordering::Equal,
+            (Comparison::Ne, Some(ordering)) => ordering != Ordering::Equal,
+            (Comparison::Lt, Some(ordering)) => ordering == Ordering::Less,
+            (Comparison::Le, Some(ordering)) => ordering != Ordering::Greater,
+            (Comparison::Gt, Some(ordering)) => ordering == Ordering::Greater,
+            (Comparison::Ge, Some(ordering)) => ordering != Ordering::Less,
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let symbol = match self {
+            Comparison::Eq => "==",
+            Comparison::Ne => "!=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        };
+        f.write_str(symbol)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Number,
+    Boolean,
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Type::Number => f.write_str("number"),
+            Type::Boolean => f.write_str("boolean"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<N = f64> {
+    Num(N),
+    Bool(bool),
+}
+
+impl<N> Value<N> {
+    pub fn value_type(&self) -> Type {
+        match self {
+            Value::Num(_) => Type::Number,
+            Value::Bool(_) => Type::Boolean,
+        }
+    }
+
+    pub fn into_num(self) -> Result<N, EvalError> {
+        match self {
+            Value::Num(num) => Ok(num),
+            Value::Bool(_) => Err(EvalError::TypeMismatch {
+                expected: Type::Number,
+                found: Type::Boolean,
+            }),
+        }
+    }
+
+    pub fn into_bool(self) -> Result<bool, EvalError> {
+        match self {
+            Value::Bool(value) => Ok(value),
+            Value::Num(_) => Err(EvalError::TypeMismatch {
+                expected: Type::Boolean,
+                found: Type::Number,
+            }),
+        }
+    }
+}
+
+impl<N: Display> Display for Value<N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Value::Num(num) => write!(f, "{}", num),
+            Value::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Func {
+    Sin,
+    Cos,
+    Tan,
+    Exp,
+    Ln,
+    Sqrt,
+}
+
+impl Func {
+    pub fn apply(self, arg: f64) -> f64 {
+        match self {
+            Func::Sin => arg.sin(),
+            Func::Cos => arg.cos(),
+            Func::Tan => arg.tan(),
+            Func::Exp => arg.exp(),
+            Func::Ln => arg.ln(),
+            Func::Sqrt => arg.sqrt(),
+        }
+    }
+}
+
+impl FromStr for Func {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "sin" => Ok(Func::Sin),
+            "cos" => Ok(Func::Cos),
+            "tan" => Ok(Func::Tan),
+            "exp" => Ok(Func::Exp),
+            "ln" => Ok(Func::Ln),
+            "sqrt" => Ok(Func::Sqrt),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Func {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let name = match self {
+            Func::Sin => "sin",
+            Func::Cos => "cos",
+            Func::Tan => "tan",
+            Func::Exp => "exp",
+            Func::Ln => "ln",
+            Func::Sqrt => "sqrt",
+        };
+        f.write_str(name)
+    }
+}
+
+impl<N> Ast<N> {
+    pub fn num(num: N) -> Self {
+        Ast::Num(num)
+    }
+
+    pub fn var(name: &str) -> Self {
+        Ast::Var(name.to_owned())
+    }
+
+    pub fn negate(operand: Ast<N>) -> Self {
+        Ast::Neg(Box::new(operand))
+    }
+
+    pub fn op(op: char, left: Ast<N>, right: Ast<N>) -> Self {
+        Ast::Op(op, Box::new(left), Box::new(right))
+    }
+
+    pub fn call(func: Func, arg: Ast<N>) -> Self {
+        Ast::Call(func, Box::new(arg))
+    }
+
+    pub fn logical_not(operand: Ast<N>) -> Self {
+        Ast::Not(Box::new(operand))
+    }
+
+    pub fn compare(comparison: Comparison, left: Ast<N>, right: Ast<N>) -> Self {
+        Ast::Cmp(comparison, Box::new(left), Box::new(right))
+    }
+
+    pub fn and(left: Ast<N>, right: Ast<N>) -> Self {
+        Ast::And(Box::new(left), Box::new(right))
+    }
+
+    pub fn or(left: Ast<N>, right: Ast<N>) -> Self {
+        Ast::Or(Box::new(left), Box::new(right))
+    }
+
+    pub fn conditional(condition: Ast<N>, then: Ast<N>, otherwise: Ast<N>) -> Self {
+        Ast::If(Box::new(condition), Box::new(then), Box::new(otherwise))
+    }
+
+    pub fn convert(operand: Ast<N>, unit: Unit) -> Self {
+        Ast::Convert(Box::new(operand), unit)
+    }
+
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = vec![];
+        self.collect_variables(&mut names);
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    fn collect_variables<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Ast::Num(_) | Ast::Bool(_) => {}
+            Ast::Var(name) => names.push(name),
+            Ast::Neg(operand)
+            | Ast::Call(_, operand)
+            | Ast::Not(operand)
+            | Ast::Convert(operand, _) => operand.collect_variables(names),
+            Ast::Op(_, left, right)
+            | Ast::Cmp(_, left, right)
+            | Ast::And(left, right)
+            | Ast::Or(left, right) => {
+                left.collect_variables(names);
+                right.collect_variables(names);
+            }
+            Ast::If(condition, then, otherwise) => {
+                condition.collect_variables(names);
+                then.collect_variables(names);
+                otherwise.collect_variables(names);
+            }
+        }
+    }
+
+    pub fn check(&self) -> Result<Type, EvalError> {
+        match self {
+            Ast::Num(_) | Ast::Var(_) => Ok(Type::Number),
+            Ast::Bool(_) => Ok(Type::Boolean),
+            Ast::Neg(operand) | Ast::Call(_, operand) | Ast::Convert(operand, _) => {
+                operand.expect(Type::Number)?;
+                Ok(Type::Number)
+            }
+            Ast::Op(_, left, right) => {
+                left.expect(Type::Number)?;
+                right.expect(Type::Number)?;
+                Ok(Type::Number)
+            }
+            Ast::Cmp(_, left, right) => {
+                left.expect(Type::Number)?;
+                right.expect(Type::Number)?;
+                Ok(Type::Boolean)
+            }
+            Ast::Not(operand) => {
+                operand.expect(Type::Boolean)?;
+                Ok(Type::Boolean)
+            }
+            Ast::And(left, right) | Ast::Or(left, right) => {
+                left.expect(Type::Boolean)?;
+                right.expect(Type::Boolean)?;
+                Ok(Type::Boolean)
+            }
+            Ast::If(condition, then, otherwise) => {
+                condition.expect(Type::Boolean)?;
+                let result = then.check()?;
+                otherwise.expect(result)?;
+                Ok(result)
+            }
+        }
+    }
+
+    fn expect(&self, expected: Type) -> Result<(), EvalError> {
+        match self.check()? {
+            found if found == expected => Ok(()),
+            found => Err(EvalError::TypeMismatch { expected, found }),
+        }
+    }
+}
+
+impl<N: Number> Ast<N> {
+    pub fn eval(&self, vars: &HashMap<String, N>) -> Result<N, EvalError> {
+        self.evaluate(vars)?.into_num()
+    }
+
+    pub fn evaluate(&self, vars: &HashMap<String, N>) -> Result<Value<N>, EvalError> {
+        let value = match self {
+            Ast::Num(num) => Value::Num(num.clone()),
+            Ast::Var(name) => vars
+                .get(name)
+                .cloned()
+                .map(Value::Num)
+                .ok_or_else(|| EvalError::UnknownVariable(name.clone()))?,
+            Ast::Neg(operand) => Value::Num(operand.eval(vars)?.negate()?),
+            Ast::Op(op, left, right) => {
+                Value::Num(left.eval(vars)?.apply_op(*op, &right.eval(vars)?)?)
+            }
+            Ast::Call(func, arg) => Value::Num(arg.eval(vars)?.apply_func(*func)?),
+            Ast::Bool(value) => Value::Bool(*value),
+            Ast::Not(operand) => Value::Bool(!operand.test(vars)?),
+            Ast::Cmp(comparison, left, right) => {
+                Value::Bool(left.eval(vars)?.compare(*comparison, &right.eval(vars)?)?)
+            }
+            Ast::And(left, right) => Value::Bool(left.test(vars)? && right.test(vars)?),
+            Ast::Or(left, right) => Value::Bool(left.test(vars)? || right.test(vars)?),
+            Ast::If(condition, then, otherwise) => {
+                return if condition.test(vars)? {
+                    then.evaluate(vars)
+                } else {
+                    otherwise.evaluate(vars)
+                }
+            }
+            Ast::Convert(operand, unit) => Value::Num(operand.eval(vars)?.convert(unit)?),
+        };
+        Ok(value)
+    }
+
+    fn test(&self, vars: &HashMap<String, N>) -> Result<bool, EvalError> {
+        self.evaluate(vars)?.into_bool()
+    }
+
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            Ast::Convert(_, _) => 0,
+            Ast::If(_, _, _) => 1,
+            Ast::Or(_, _) => 2,
+            Ast::And(_, _) => 3,
+            Ast::Cmp(_, _, _) => 4,
+            Ast::Op('+', _, _) | Ast::Op('-', _, _) => 5,
+            Ast::Op('*', _, _) | Ast::Op('/', _, _) => 6,
+            Ast::Neg(_) | Ast::Not(_) => 7,
+            Ast::Num(num) if num.is_negative() => 7,
+            Ast::Op(_, _, _) => 8,
+            Ast::Num(_) | Ast::Var(_) | Ast::Call(_, _) | Ast::Bool(_) => 9,
+        }
+    }
+}
+
+impl<N: Number> Display for Ast<N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Ast::Num(num) => write!(f, "{}", num),
+            Ast::Var(name) => f.write_str(name),
+            Ast::Neg(operand) => {
+                f.write_str("-")?;
+                write_operand(f, operand, operand.precedence() < self.precedence())
+            }
+            Ast::Op(op, left, right) => {
+                let precedence = self.precedence();
+                let (left_parens, right_parens) = if *op == '^' {
+                    (left.precedence() <= precedence, right.precedence() < 7)
+                } else {
+                    (
+                        left.precedence() < precedence,
+                        right.precedence() < precedence
+                            || (right.precedence() == precedence && (*op == '-' || *op == '/')),
+                    )
+                };
+                write_operand(f, left, left_parens)?;
+                write!(f, " {} ", op)?;
+                write_operand(f, right, right_parens)
+            }
+            Ast::Call(func, arg) => write!(f, "{}({})", func, arg),
+            Ast::Bool(value) => write!(f, "{}", value),
+            Ast::Not(operand) => {
+                f.write_str("!")?;
+                write_operand(f, operand, operand.precedence() < self.precedence())
+            }
+            Ast::Cmp(comparison, left, right) => {
+                write_operand(f, left, left.precedence() <= self.precedence())?;
+                write!(f, " {} ", comparison)?;
+                write_operand(f, right, right.precedence() <= self.precedence())
+            }
+            Ast::And(left, right) | Ast::Or(left, right) => {
+                let symbol = if let Ast::And(_, _) = self {
+                    "&&"
+                } else {
+                    "||"
+                };
+                write_operand(f, left, left.precedence() < self.precedence())?;
+                write!(f, " {} ", symbol)?;
+                write_operand(f, right, right.precedence() < self.precedence())
+            }
+            Ast::If(condition, then, otherwise) => {
+                write_operand(f, condition, condition.precedence() <= self.precedence())?;
+                f.write_str(" ? ")?;
+                write_operand(f, then, then.precedence() < self.precedence())?;
+                f.write_str(" : ")?;
+                write_operand(f, otherwise, otherwise.precedence() < self.precedence())
+            }
+            Ast::Convert(operand, unit) => write!(f, "{} to {}", operand, unit),
+        }
+    }
+}
+
+fn write_operand<N: Number>(f: &mut Formatter, operand: &Ast<N>, parens: bool) -> fmt::Result {
+    if parens {
+        write!(f, "({})", operand)
+    } else {
+        write!(f, "{}", operand)
+    }
+}
+
+struct Parser<'p, N> {
+    cursor: Cursor<'p>,
+    len: usize,
+    depth: usize,
+    phantom: PhantomData<N>,
+}
+
+impl<'p, N: Number> Parser<'p, N> {
+    fn new(source: &'p str) -> Self {
+        Parser {
+            cursor: source.chars().enumerate().peekable(),
+            len: source.chars().count(),
+            depth: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    fn index(&mut self) -> usize {
+        match self.cursor.peek() {
+            Some(&(index, _)) => index,
+            None => self.len,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&(_, char)) = self.cursor.peek() {
+            if !char.is_whitespace() {
+                break;
+            }
+            self.cursor.next();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.cursor.peek().map(|&(_, char)| char)
+    }
+
+    fn next_if(&mut self, expected: &[char]) -> Option<char> {
+        match self.peek() {
+            Some(char) if expected.contains(&char) => self.cursor.next().map(|(_, char)| char),
+            _ => None,
+        }
+    }
+
+    fn error(&mut self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            position: self.index(),
+        }
+    }
+
+    fn unexpected(&mut self) -> ParseError {
+        let kind = match self.peek() {
+            None => ParseErrorKind::UnexpectedEnd,
+            Some(')') if self.depth == 0 => ParseErrorKind::UnmatchedParenthesis,
+            Some(char) => ParseErrorKind::UnexpectedChar(char),
+        };
+        self.error(kind)
+    }
+
+    fn open_parenthesis(&mut self) -> usize {
+        let index = self.index();
+        self.cursor.next();
+        self.depth += 1;
+        index
+    }
+
+    fn close_parenthesis(&mut self, open: usize) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(')') => {
+                self.cursor.next();
+                self.depth -= 1;
+                Ok(())
+            }
+            None => Err(ParseError {
+                kind: ParseErrorKind::UnclosedParenthesis,
+                position: open,
+            }),
+            Some(_) => Err(self.unexpected()),
+        }
+    }
+
+    fn next_symbol(&mut self, expected: &[&'static str]) -> Option<&'static str> {
+        self.skip_whitespace();
+        for symbol in expected {
+            let mut cursor = self.cursor.clone();
+            if symbol
+                .chars()
+                .all(|char| cursor.next().map(|(_, next)| next) == Some(char))
+            {
+                self.cursor = cursor;
+                return Some(symbol);
+            }
+        }
+        None
+    }
+
+    fn expect_keyword(&mut self, keyword: &'static str) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        let mut cursor = self.cursor.clone();
+        if read_word(&mut cursor) == keyword {
+            self.cursor = cursor;
+            Ok(())
+        } else {
+            Err(self.error(ParseErrorKind::Expected(keyword)))
+        }
+    }
+
+    fn next_unit(&mut self) -> Option<Unit> {
+        self.skip_whitespace();
+        let mut cursor = self.cursor.clone();
+        let unit = Unit::lookup(&read_word(&mut cursor))?;
+        self.cursor = cursor;
+        let mut cursor = self.cursor.clone();
+        if cursor.next_if(|&(_, char)| char == '^').is_some() {
+            let mut power = String::new();
+            if cursor.next_if(|&(_, char)| char == '-').is_some() {
+                power.push('-');
+            }
+            while let Some((_, digit)) = cursor.next_if(|(_, char)| char.is_ascii_digit()) {
+                power.push(digit);
+            }
+            if let Some(power) = power.parse().ok().and_then(|power| unit.powi(power)) {
+                self.cursor = cursor;
+                return Some(power);
+            }
+        }
+        Some(unit)
+    }
+
+    fn parse_unit(&mut self) -> Option<Unit> {
+        let mut unit = self.next_unit()?;
+        loop {
+            let checkpoint = self.cursor.clone();
+            let next = match self.next_if(&['*', '/']) {
+                Some(op) => self.next_unit().map(|next| (op, next)),
+                None => None,
+            };
+            // a product whose exponents overflow ends the unit, like a power that doesn't fit
+            let combined = match next {
+                Some(('*', next)) => unit.mul(&next),
+                Some((_, next)) => unit.div(&next),
+                None => None,
+            };
+            unit = match combined {
+                Some(combined) => combined,
+                None => {
+                    self.cursor = checkpoint;
+                    return Some(unit);
+                }
+            };
+        }
+    }
+
+    fn parse(&mut self) -> Result<Ast<N>, ParseError> {
+        let root = self.parse_expression()?;
+        match self.peek() {
+            None => Ok(root),
+            Some(_) => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_expression(&mut self) -> Result<Ast<N>, ParseError> {
+        let mut root = self.parse_conditional()?;
+        while self.expect_keyword("to").is_ok() {
+            let unit = match self.parse_unit() {
+                Some(unit) => unit,
+                None => {
+                    let word = read_word(&mut self.cursor.clone());
+                    let kind = if word.is_empty() {
+                        ParseErrorKind::Expected("unit")
+                    } else {
+                        ParseErrorKind::UnknownUnit(word)
+                    };
+                    return Err(self.error(kind));
+                }
+            };
+            root = Ast::convert(root, unit);
+        }
+        Ok(root)
+    }
+
+    fn parse_conditional(&mut self) -> Result<Ast<N>, ParseError> {
+        let condition = self.parse_or()?;
+        if self.next_if(&['?']).is_none() {
+            return Ok(condition);
+        }
+        let then = self.parse_conditional()?;
+        if self.next_if(&[':']).is_none() {
+            return Err(self.error(ParseErrorKind::Expected(":")));
+        }
+        let otherwise = self.parse_conditional()?;
+        Ok(Ast::conditional(condition, then, otherwise))
+    }
+
+    fn parse_or(&mut self) -> Result<Ast<N>, ParseError> {
+        let mut root = self.parse_and()?;
+        while self.next_symbol(&["||"]).is_some() {
+            root = Ast::or(root, self.parse_and()?);
+        }
+        Ok(root)
+    }
+
+    fn parse_and(&mut self) -> Result<Ast<N>, ParseError> {
+        let mut root = self.parse_comparison()?;
+        while self.next_symbol(&["&&"]).is_some() {
+            root = Ast::and(root, self.parse_comparison()?);
+        }
+        Ok(root)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Ast<N>, ParseError> {
+        let left = self.parse_sum()?;
+        let comparison = match self.next_symbol(&["==", "!=", "<=", ">=", "<", ">"]) {
+            Some("==") => Comparison::Eq,
+            Some("!=") => Comparison::Ne,
+            Some("<=") => Comparison::Le,
+            Some(">=") => Comparison::Ge,
+            Some("<") => Comparison::Lt,
+            Some(">") => Comparison::Gt,
+            _ => return Ok(left),
+        };
+        Ok(Ast::compare(comparison, left, self.parse_sum()?))
+    }
+
+    fn parse_sum(&mut self) -> Result<Ast<N>, ParseError> {
+        let mut root = self.parse_term()?;
+        while let Some(op) = self.next_if(&['+', '-']) {
+            root = Ast::op(op, root, self.parse_term()?);
+        }
+        Ok(root)
+    }
+
+    fn parse_term(&mut self) -> Result<Ast<N>, ParseError> {
+        let mut root = self.parse_unary()?;
+        while let Some(op) = self.next_if(&['*', '/', '×', '÷']) {
+            let op = match op {
+                '×' => '*',
+                '÷' => '/',
+                op => op,
+            };
+            root = Ast::op(op, root, self.parse_unary()?);
+        }
+        Ok(root)
+    }
+
+    fn parse_unary(&mut self) -> Result<Ast<N>, ParseError> {
+        match self.next_if(&['-', '!']) {
+            Some('-') => match self.parse_unary()? {
+                Ast::Num(num) => Ok(match num.negate() {
+                    Ok(negated) => Ast::Num(negated),
+                    Err(_) => Ast::negate(Ast::Num(num)),
+                }),
+                operand => Ok(Ast::negate(operand)),
+            },
+            Some(_) => Ok(Ast::logical_not(self.parse_unary()?)),
+            None => self.parse_power(),
+        }
+    }
+
+    fn parse_power(&mut self) -> Result<Ast<N>, ParseError> {
+        let base = self.parse_atom()?;
+        match self.next_if(&['^']) {
+            Some(op) => Ok(Ast::op(op, base, self.parse_unary()?)),
+            None => Ok(base),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Ast<N>, ParseError> {
+        match self.peek() {
+            Some('(') => {
+                let open = self.open_parenthesis();
+                let root = self.parse_expression()?;
+                self.close_parenthesis(open)?;
+                Ok(root)
+            }
+            Some(char) if char.is_ascii_digit() || char == '.' => self.parse_num(),
+            Some(char) if char.is_alphabetic() || char == '_' => self.parse_identifier(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_num(&mut self) -> Result<Ast<N>, ParseError> {
+        let index = self.index();
+        let mut num = String::new();
+        while let Some(&(_, char)) = self.cursor.peek() {
+            if !char.is_ascii_digit() && char != '.' {
+                break;
+            }
+            num.push(char);
+            self.cursor.next();
+        }
+        let num: N = num.parse().map_err(|_| ParseError {
+            kind: ParseErrorKind::InvalidNumber(num.clone()),
+            position: index,
+        })?;
+        self.skip_whitespace();
+        let index = self.index();
+        match self.parse_unit() {
+            Some(unit) => num.with_unit(&unit).map(Ast::Num).ok_or(ParseError {
+                kind: ParseErrorKind::UnsupportedUnit(unit.to_string()),
+                position: index,
+            }),
+            None => Ok(Ast::Num(num)),
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<Ast<N>, ParseError> {
+        let index = self.index();
+        let name = read_word(&mut self.cursor);
+        match name.as_str() {
+            "true" => return Ok(Ast::Bool(true)),
+            "false" => return Ok(Ast::Bool(false)),
+            "if" => return self.parse_if(),
+            "then" | "else" | "to" => {
+                return Err(ParseError {
+                    kind: ParseErrorKind::ReservedWord(name),
+                    position: index,
+                })
+            }
+            _ => {}
+        }
+        if self.peek() == Some('(') {
+            let func = name.parse().map_err(|_| ParseError {
+                kind: ParseErrorKind::UnknownFunction(name.clone()),
+                position: index,
+            })?;
+            let open = self.open_parenthesis();
+            let arg = self.parse_expression()?;
+            self.close_parenthesis(open)?;
+            Ok(Ast::call(func, arg))
+        } else {
+            Ok(Ast::Var(name))
+        }
+    }
+
+    fn parse_if(&mut self) -> Result<Ast<N>, ParseError> {
+        let condition = self.parse_expression()?;
+        self.expect_keyword("then")?;
+        let then = self.parse_expression()?;
+        self.expect_keyword("else")?;
+        let otherwise = self.parse_expression()?;
+        Ok(Ast::conditional(condition, then, otherwise))
+    }
+}
+
+fn read_word(cursor: &mut Cursor) -> String {
+    let mut word = String::new();
+    while let Some((_, char)) = cursor.next_if(|(_, char)| char.is_alphanumeric() || *char == '_') {
+        word.push(char);
+    }
+    word
+}
+
+impl<N: Number> FromStr for Ast<N> {
+    type Err = ParseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Parser::new(source).parse()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedChar(char),
+    UnexpectedEnd,
+    UnclosedParenthesis,
+    UnmatchedParenthesis,
+    InvalidNumber(String),
+    UnknownFunction(String),
+    UnknownUnit(String),
+    UnsupportedUnit(String),
+    ReservedWord(String),
+    Expected(&'static str),
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::UnexpectedChar(char) => write!(f, "unexpected `{}`", char),
+            ParseErrorKind::UnexpectedEnd => f.write_str("unexpected end of input"),
+            ParseErrorKind::UnclosedParenthesis => f.write_str("unclosed parenthesis"),
+            ParseErrorKind::UnmatchedParenthesis => f.write_str("unmatched closing parenthesis"),
+            ParseErrorKind::InvalidNumber(num) => write!(f, "invalid number `{}`", num),
+            ParseErrorKind::UnknownFunction(name) => write!(f, "unknown function `{}`", name),
+            ParseErrorKind::UnknownUnit(name) => write!(f, "unknown unit `{}`", name),
+            ParseErrorKind::UnsupportedUnit(unit) => {
+                write!(f, "unit `{}` needs a number type with units", unit)
+            }
+            ParseErrorKind::ReservedWord(word) => write!(f, "`{}` is a reserved word", word),
+            ParseErrorKind::Expected(what) => write!(f, "expected `{}`", what),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    position: usize,
+}
+
+impl ParseError {
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} at column {}", self.kind, self.position + 1)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EvalError {
+    UnknownVariable(String),
+    UnknownOperator(char),
+    DivisionByZero,
+    Overflow,
+    NotRepresentable(String),
+    TypeMismatch { expected: Type, found: Type },
+    Indeterminate(String),
+    IncompatibleUnits(String, String),
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            EvalError::UnknownVariable(name) => write!(f, "unknown variable `{}`", name),
+            EvalError::UnknownOperator(op) => write!(f, "unknown operator `{}`", op),
+            EvalError::DivisionByZero => f.write_str("division by zero"),
+            EvalError::Overflow => f.write_str("number too large"),
+            EvalError::NotRepresentable(expression) => {
+                write!(f, "`{}` has no value in this number type", expression)
+            }
+            EvalError::TypeMismatch { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            EvalError::Indeterminate(comparison) => {
+                write!(f, "`{}` is neither true nor false", comparison)
+            }
+            EvalError::IncompatibleUnits(left, right) => {
+                write!(f, "can't combine `{}` with `{}`", left, right)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(kind: ParseErrorKind, position: usize) -> Result<Ast, ParseError> {
+        Err(ParseError { kind, position })
+    }
+
+    fn vars(bindings: &[(&str, f64)]) -> HashMap<String, f64> {
+        bindings
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value))
+            .collect()
+    }
+
+    #[test]
+    fn number() {
+        assert_eq!("4.25".parse(), Ok(Ast::num(4.25)))
+    }
+
+    #[test]
+    fn negative_number() {
+        assert_eq!("-5".parse(), Ok(Ast::num(-5.0)))
+    }
+
+    #[test]
+    fn variable() {
+        assert_eq!("rate_2".parse(), Ok(Ast::<f64>::var("rate_2")))
+    }
+
+    #[test]
+    fn whitespaces_are_skipped() {
+        assert_eq!(
+            " x +  2 ".parse(),
+            Ok(Ast::op('+', Ast::var("x"), Ast::num(2.0)))
+        )
+    }
+
+    #[test]
+    fn operator_precedence() {
+        assert_eq!(
+            "1+2*x^3".parse(),
+            Ok(Ast::op(
+                '+',
+                Ast::num(1.0),
+                Ast::op(
+                    '*',
+                    Ast::num(2.0),
+                    Ast::op('^', Ast::var("x"), Ast::num(3.0))
+                )
+            ))
+        )
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(
+            "2^3^x".parse(),
+            Ok(Ast::op(
+                '^',
+                Ast::num(2.0),
+                Ast::op('^', Ast::num(3.0), Ast::var("x"))
+            ))
+        )
+    }
+
+    #[test]
+    fn unary_minus_binds_weaker_than_power() {
+        assert_eq!(
+            "-x^2".parse(),
+            Ok(Ast::negate(Ast::op('^', Ast::var("x"), Ast::num(2.0))))
+        )
+    }
+
+    #[test]
+    fn parenthesis() {
+        assert_eq!(
+            "(1-x)*2".parse(),
+            Ok(Ast::op(
+                '*',
+                Ast::op('-', Ast::num(1.0), Ast::var("x")),
+                Ast::num(2.0)
+            ))
+        )
+    }
+
+    #[test]
+    fn function_call() {
+        assert_eq!(
+            "sin(x*2)".parse(),
+            Ok(Ast::call(
+                Func::Sin,
+                Ast::op('*', Ast::var("x"), Ast::num(2.0))
+            ))
+        )
+    }
+
+    #[test]
+    fn unknown_function() {
+        assert_eq!(
+            Ast::<f64>::from_str("1+foo(x)"),
+            failure(ParseErrorKind::UnknownFunction("foo".to_owned()), 2)
+        )
+    }
+
+    #[test]
+    fn unknown_operator() {
+        assert_eq!(
+            Ast::<f64>::from_str("5&4"),
+            failure(ParseErrorKind::UnexpectedChar('&'), 1)
+        )
+    }
+
+    #[test]
+    fn missing_operand() {
+        assert_eq!(
+            Ast::<f64>::from_str("5+"),
+            failure(ParseErrorKind::UnexpectedEnd, 2)
+        )
+    }
+
+    #[test]
+    fn unclosed_parenthesis() {
+        assert_eq!(
+            Ast::<f64>::from_str("(1+2"),
+            failure(ParseErrorKind::UnclosedParenthesis, 0)
+        )
+    }
+
+    #[test]
+    fn unmatched_parenthesis() {
+        assert_eq!(
+            Ast::<f64>::from_str("(1+2))"),
+            failure(ParseErrorKind::UnmatchedParenthesis, 5)
+        );
+        assert_eq!(
+            Ast::<f64>::from_str("1+2)*3"),
+            failure(ParseErrorKind::UnmatchedParenthesis, 3)
+        );
+    }
+
+    #[test]
+    fn unclosed_call_points_at_its_parenthesis() {
+        assert_eq!(
+            Ast::<f64>::from_str("sin((x)"),
+            failure(ParseErrorKind::UnclosedParenthesis, 3)
+        )
+    }
+
+    #[test]
+    fn multiplication_and_division_glyphs() {
+        assert_eq!("2 × 3 ÷ x".parse(), Ast::<f64>::from_str("2 * 3 / x"))
+    }
+
+    #[test]
+    fn parse_error_message() {
+        assert_eq!(
+            Ast::<f64>::from_str("1 + (2").unwrap_err().to_string(),
+            "unclosed parenthesis at column 5"
+        )
+    }
+
+    #[test]
+    fn eval_error_message() {
+        assert_eq!(
+            EvalError::IncompatibleUnits("s".to_owned(), "m".to_owned()).to_string(),
+            "can't combine `s` with `m`"
+        );
+        assert_eq!(EvalError::Overflow.to_string(), "number too large");
+    }
+
+    #[test]
+    fn evaluate_with_variables() {
+        let ast: Ast = "price * qty - 2^3".parse().unwrap();
+
+        assert_eq!(ast.eval(&vars(&[("price", 2.5), ("qty", 4.0)])), Ok(2.0));
+    }
+
+    #[test]
+    fn evaluate_function() {
+        let ast: Ast = "sqrt(x) + ln(1)".parse().unwrap();
+
+        assert_eq!(ast.eval(&vars(&[("x", 16.0)])), Ok(4.0));
+    }
+
+    #[test]
+    fn evaluate_unknown_variable() {
+        let ast: Ast = "x + y".parse().unwrap();
+
+        assert_eq!(
+            ast.eval(&vars(&[("x", 1.0)])),
+            Err(EvalError::UnknownVariable("y".to_owned()))
+        );
+    }
+
+    #[test]
+    fn variables() {
+        let ast: Ast = "y * x + sin(y)".parse().unwrap();
+
+        assert_eq!(ast.variables(), vec!["x", "y"]);
+    }
+
+    #[test]
+    fn comparison_binds_weaker_than_arithmetic() {
+        assert_eq!(
+            "x + 1 <= 2 * y".parse(),
+            Ok(Ast::compare(
+                Comparison::Le,
+                Ast::op('+', Ast::var("x"), Ast::num(1.0)),
+                Ast::op('*', Ast::num(2.0), Ast::var("y"))
+            ))
+        )
+    }
+
+    #[test]
+    fn comparisons_do_not_chain() {
+        assert_eq!(
+            Ast::<f64>::from_str("1 < x < 3"),
+            failure(ParseErrorKind::UnexpectedChar('<'), 6)
+        )
+    }
+
+    #[test]
+    fn logical_operators_precedence() {
+        assert_eq!(
+            "!a == 1 || b != 2 && true".parse(),
+            Ok(Ast::or(
+                Ast::compare(
+                    Comparison::Eq,
+                    Ast::logical_not(Ast::var("a")),
+                    Ast::num(1.0)
+                ),
+                Ast::and(
+                    Ast::compare(Comparison::Ne, Ast::var("b"), Ast::num(2.0)),
+                    Ast::Bool(true)
+                )
+            ))
+        )
+    }
+
+    #[test]
+    fn ternary_conditional() {
+        assert_eq!(
+            "qty > 100 ? price * 0.9 : price".parse(),
+            Ok(Ast::conditional(
+                Ast::compare(Comparison::Gt, Ast::var("qty"), Ast::num(100.0)),
+                Ast::op('*', Ast::var("price"), Ast::num(0.9)),
+                Ast::var("price")
+            ))
+        )
+    }
+
+    #[test]
+    fn if_then_else_is_the_same_conditional() {
+        assert_eq!(
+            Ast::<f64>::from_str("if qty > 100 then price * 0.9 else price"),
+            "qty > 100 ? price * 0.9 : price".parse()
+        )
+    }
+
+    #[test]
+    fn if_without_else() {
+        assert_eq!(
+            Ast::<f64>::from_str("if x then 1"),
+            failure(ParseErrorKind::Expected("else"), 11)
+        )
+    }
+
+    #[test]
+    fn ternary_without_colon() {
+        assert_eq!(
+            Ast::<f64>::from_str("x ? 1 2"),
+            failure(ParseErrorKind::Expected(":"), 6)
+        )
+    }
+
+    #[test]
+    fn evaluate_pricing_rule() {
+        let ast: Ast = "qty > 100 ? price * 0.5 : price".parse().unwrap();
+
+        assert_eq!(ast.eval(&vars(&[("qty", 150.0), ("price", 3.0)])), Ok(1.5));
+        assert_eq!(ast.eval(&vars(&[("qty", 100.0), ("price", 3.0)])), Ok(3.0));
+    }
+
+    #[test]
+    fn evaluate_comparisons() {
+        for &(source, expected) in &[
+            ("1 == 1", true),
+            ("1 != 1", false),
+            ("1 < 2", true),
+            ("2 <= 2", true),
+            ("1 > 2", false),
+            ("1 >= 2", false),
+            ("!(1 < 2)", false),
+            ("1 < 2 && 2 < 1", false),
+            ("1 < 2 || 2 < 1", true),
+        ] {
+            let ast: Ast = source.parse().unwrap();
+
+            assert_eq!(
+                ast.evaluate(&HashMap::new()),
+                Ok(Value::Bool(expected)),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn nan_is_unordered() {
+        let ast: Ast = "x == x || x < 0 || x >= 0".parse().unwrap();
+
+        assert_eq!(
+            ast.evaluate(&vars(&[("x", f64::NAN)])),
+            Ok(Value::Bool(false))
+        );
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        let and: Ast = "false && y > 0".parse().unwrap();
+        let or: Ast = "true || y > 0".parse().unwrap();
+        let conditional: Ast = "x > 0 ? x : y".parse().unwrap();
+
+        assert_eq!(and.evaluate(&HashMap::new()), Ok(Value::Bool(false)));
+        assert_eq!(or.evaluate(&HashMap::new()), Ok(Value::Bool(true)));
+        assert_eq!(conditional.eval(&vars(&[("x", 2.0)])), Ok(2.0));
+    }
+
+    #[test]
+    fn type_errors() {
+        for &(source, expected, found) in &[
+            ("1 + (2 < 3)", Type::Number, Type::Boolean),
+            ("!1", Type::Boolean, Type::Number),
+            ("1 && true", Type::Boolean, Type::Number),
+            ("1 ? 2 : 3", Type::Boolean, Type::Number),
+            ("-true", Type::Number, Type::Boolean),
+        ] {
+            let ast: Ast = source.parse().unwrap();
+
+            assert_eq!(
+                ast.evaluate(&HashMap::new()),
+                Err(EvalError::TypeMismatch { expected, found }),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn eval_expects_a_number() {
+        let ast: Ast = "1 < 2".parse().unwrap();
+
+        assert_eq!(
+            ast.eval(&HashMap::new()),
+            Err(EvalError::TypeMismatch {
+                expected: Type::Number,
+                found: Type::Boolean,
+            })
+        );
+    }
+
+    #[test]
+    fn check_types_without_evaluating() {
+        let number: Ast = "x > 0 ? x : -x".parse().unwrap();
+        let boolean: Ast = "x > 0 || y".parse().unwrap();
+        let mixed: Ast = "x > 0 ? x : x > 1".parse().unwrap();
+
+        assert_eq!(number.check(), Ok(Type::Number));
+        assert_eq!(
+            boolean.check(),
+            Err(EvalError::TypeMismatch {
+                expected: Type::Boolean,
+                found: Type::Number,
+            })
+        );
+        assert_eq!(
+            mixed.check(),
+            Err(EvalError::TypeMismatch {
+                expected: Type::Number,
+                found: Type::Boolean,
+            })
+        );
+    }
+
+    #[test]
+    fn units_need_a_number_that_carries_them() {
+        assert_eq!(
+            Ast::<f64>::from_str("5 km"),
+            failure(ParseErrorKind::UnsupportedUnit("km".to_owned()), 2)
+        )
+    }
+
+    #[test]
+    fn unit_after_number_stops_before_operand() {
+        assert_eq!(
+            "x * 2 / y".parse(),
+            Ok(Ast::op(
+                '/',
+                Ast::op('*', Ast::var("x"), Ast::num(2.0)),
+                Ast::var("y")
+            ))
+        )
+    }
+
+    #[test]
+    fn conversion_binds_weakest() {
+        assert_eq!(
+            "x > 1 ? x : 1 to km".parse(),
+            Ok(Ast::convert(
+                Ast::conditional(
+                    Ast::compare(Comparison::Gt, Ast::var("x"), Ast::num(1.0)),
+                    Ast::var("x"),
+                    Ast::num(1.0)
+                ),
+                "km".parse().unwrap()
+            ))
+        )
+    }
+
+    #[test]
+    fn conversion_to_unknown_unit() {
+        assert_eq!(
+            Ast::<f64>::from_str("x to parsec"),
+            failure(ParseErrorKind::UnknownUnit("parsec".to_owned()), 5)
+        )
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for source in &[
+            "1 + 2 * x",
+            "(1 + 2) * x",
+            "x - (y - z)",
+            "x / (y * z)",
+            "-(x + 1)",
+            "(-2) ^ x",
+            "2 ^ 3 ^ x",
+            "(2 ^ 3) ^ x",
+            "-x ^ 2",
+            "cos(x - 1)",
+            "x + 1 < y * 2",
+            "(x < 1) == (y < 1)",
+            "a > 0 || b > 0 && !(c > 0)",
+            "(a > 0 || b > 0) && c > 0",
+            "x > 0 ? x : -x",
+            "(x > 0 ? x : y) + 1",
+            "x > 0 ? 1 : y > 0 ? 2 : 3",
+            "true || false",
+            "(x to km) + 1",
+            "x > 0 ? (x to m) : y to km",
+        ] {
+            let ast: Ast = source.parse().unwrap();
+
+            assert_eq!(ast.to_string(), *source);
+            assert_eq!(ast.to_string().parse(), Ok(ast));
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt::default()
+    }
+
+    pub fn one() -> Self {
+        BigInt::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> Self {
+        BigInt {
+            negative: false,
+            magnitude: self.magnitude.clone(),
+        }
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut base = self.clone();
+        let mut result = BigInt::one();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    pub fn gcd(&self, other: &Self) -> Self {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let remainder = &a % &b;
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+
+    pub fn to_u32(&self) -> Option<u32> {
+        match self.magnitude.as_slice() {
+            [] => Some(0),
+            [value] if !self.negative => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * 4_294_967_296.0 + limb as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    fn from_magnitude(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        let negative = negative && !magnitude.is_empty();
+        BigInt {
+            negative,
+            magnitude,
+        }
+    }
+
+    fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        assert!(!divisor.is_zero(), "attempt to divide by zero");
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+        (
+            BigInt::from_magnitude(self.negative != divisor.negative, quotient),
+            BigInt::from_magnitude(self.negative, remainder),
+        )
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_magnitude(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+fn compare_magnitude(left: &[u32], right: &[u32]) -> Ordering {
+    left.len()
+        .cmp(&right.len())
+        .then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(left.len().max(right.len()) + 1);
+    let mut carry = 0u64;
+    for index in 0..left.len().max(right.len()) {
+        let digit =
+            carry + *left.get(index).unwrap_or(&0) as u64 + *right.get(index).unwrap_or(&0) as u64;
+        sum.push(digit as u32);
+        carry = digit >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+fn sub_magnitude(larger: &[u32], smaller: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(larger.len());
+    let mut borrow = 0i64;
+    for (index, &limb) in larger.iter().enumerate() {
+        let mut digit = limb as i64 - *smaller.get(index).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if digit < 0 {
+            digit += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(digit as u32);
+    }
+    difference
+}
+
+fn mul_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; left.len() + right.len()];
+    for (i, &l) in left.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &r) in right.iter().enumerate() {
+            let digit = product[i + j] as u64 + l as u64 * r as u64 + carry;
+            product[i + j] = digit as u32;
+            carry = digit >> 32;
+        }
+        product[i + right.len()] = carry as u32;
+    }
+    product
+}
+
+fn div_rem_small(dividend: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; dividend.len()];
+    let mut remainder = 0u64;
+    for (index, &limb) in dividend.iter().enumerate().rev() {
+        let current = (remainder << 32) | limb as u64;
+        quotient[index] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    (quotient, remainder as u32)
+}
+
+fn div_rem_magnitude(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = divisor {
+        let (quotient, remainder) = div_rem_small(dividend, *divisor);
+        return (quotient, vec![remainder]);
+    }
+    let mut quotient = vec![0u32; dividend.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for bit in (0..dividend.len() * 32).rev() {
+        let mut carry = (dividend[bit / 32] >> (bit % 32)) & 1;
+        for limb in remainder.iter_mut() {
+            let shifted = (*limb << 1) | carry;
+            carry = *limb >> 31;
+            *limb = shifted;
+        }
+        if carry != 0 {
+            remainder.push(carry);
+        }
+        if compare_magnitude(&remainder, divisor) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, divisor);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_magnitude(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_magnitude(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+        match compare_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_magnitude(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::from_magnitude(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_magnitude(
+            self.negative != other.negative,
+            mul_magnitude(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+impl Div for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).1
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ();
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match source.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, source),
+        };
+        if digits.is_empty() {
+            return Err(());
+        }
+        let mut magnitude = vec![];
+        for char in digits.chars() {
+            let digit = char.to_digit(10).ok_or(())?;
+            magnitude = add_magnitude(&mul_magnitude(&magnitude, &[10]), &[digit]);
+        }
+        Ok(BigInt::from_magnitude(negative, magnitude))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+        let mut chunks = vec![];
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let (quotient, chunk) = div_rem_small(&magnitude, DECIMAL_CHUNK);
+            chunks.push(chunk);
+            magnitude = BigInt::from_magnitude(false, quotient).magnitude;
+        }
+        if self.negative {
+            f.write_str("-")?;
+        }
+        let mut chunks = chunks.into_iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(source: &str) -> BigInt {
+        source.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for source in &["0", "7", "-42", "123456789012345678901234567890"] {
+            assert_eq!(big(source).to_string(), *source);
+        }
+    }
+
+    #[test]
+    fn negative_zero_is_zero() {
+        assert_eq!(big("-0"), BigInt::zero());
+    }
+
+    #[test]
+    fn addition_and_subtraction() {
+        assert_eq!(&big("4294967295") + &big("1"), big("4294967296"));
+        assert_eq!(&big("5") - &big("12"), big("-7"));
+        assert_eq!(&big("-5") + &big("12"), big("7"));
+    }
+
+    #[test]
+    fn multiplication() {
+        assert_eq!(
+            &big("123456789012345678901234567890") * &big("-987654321"),
+            big("-121932631124828532112482853211126352690")
+        );
+    }
+
+    #[test]
+    fn division_truncates_toward_zero() {
+        assert_eq!(&big("-7") / &big("2"), big("-3"));
+        assert_eq!(&big("-7") % &big("2"), big("-1"));
+        assert_eq!(
+            &big("121932631124828532112482853211126352690")
+                / &big("123456789012345678901234567890"),
+            big("987654321")
+        );
+    }
+
+    #[test]
+    fn power_and_gcd() {
+        assert_eq!(big("2").pow(100), big("1267650600228229401496703205376"));
+        assert_eq!(big("-84").gcd(&big("36")), big("12"));
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(big("-10") < big("3"));
+        assert!(big("-10") < big("-3"));
+        assert!(big("10000000000") > big("3"));
+    }
+}
@@ -0,0 +1,214 @@
+use calculator_ast_kata::{Ast, EvalError, ParseError, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+const HELP: &str = "\
+expressions:  1 + 2 * x, sqrt(x) ^ 2, -(a - b) / 4
+conditions:   qty > 100 ? price * 0.9 : price, if x != 0 && !(y < 0) then 1 / x else 0
+assignment:   name = expression
+history:      _1, _2, ... refer to previous results
+commands:
+  :ast <expr>       print the parsed tree
+  :bytecode <expr>  print the compiled program
+  :vars             list defined variables
+  :help             show this help
+  :quit             leave the calculator";
+
+#[derive(Default)]
+struct Session {
+    vars: HashMap<String, f64>,
+    history: usize,
+}
+
+impl Session {
+    fn execute(&mut self, line: &str) -> Result<Option<String>, String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        if let Some(command) = line.strip_prefix(':') {
+            return self.command(command);
+        }
+        match assignment(line) {
+            Some((name, source)) => {
+                check_name(name)?;
+                let value = self.evaluate(source)?.into_num().map_err(describe)?;
+                self.vars.insert(name.to_owned(), value);
+                Ok(Some(format!("{} = {}", name, value)))
+            }
+            None => match self.evaluate(line)? {
+                Value::Num(value) => {
+                    self.history += 1;
+                    let name = format!("_{}", self.history);
+                    self.vars.insert(name.clone(), value);
+                    Ok(Some(format!("{} = {}", name, value)))
+                }
+                Value::Bool(value) => Ok(Some(value.to_string())),
+            },
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Result<Option<String>, String> {
+        let (name, argument) = match command.find(char::is_whitespace) {
+            Some(index) => (&command[..index], command[index..].trim()),
+            None => (command, ""),
+        };
+        match name {
+            "ast" => parse(argument).map(|ast| {
+                let mut tree = String::new();
+                dump(&ast, 0, &mut tree);
+                Some(tree.trim_end().to_owned())
+            }),
+            "bytecode" => parse(argument)?
+                .compile()
+                .map(|program| Some(program.to_string().trim_end().to_owned()))
+                .map_err(describe),
+            "vars" => {
+                let mut vars = self.vars.iter().collect::<Vec<_>>();
+                vars.sort_by_key(|(name, _)| *name);
+                Ok(Some(
+                    vars.into_iter()
+                        .map(|(name, value)| format!("{} = {}", name, value))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ))
+            }
+            "help" => Ok(Some(HELP.to_owned())),
+            _ => Err(format!("unknown command `:{}`, try :help", name)),
+        }
+    }
+
+    fn evaluate(&self, source: &str) -> Result<Value, String> {
+        parse(source)?.evaluate(&self.vars).map_err(describe)
+    }
+}
+
+fn describe(error: EvalError) -> String {
+    error.to_string()
+}
+
+fn assignment(line: &str) -> Option<(&str, &str)> {
+    let index = line.find('=')?;
+    let name = line[..index].trim();
+    let source = &line[index + 1..];
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if is_identifier && !source.starts_with('=') {
+        Some((name, source))
+    } else {
+        None
+    }
+}
+
+// history names are only written by the session, and keywords would parse as
+// something other than the variable they are meant to define
+fn check_name(name: &str) -> Result<(), String> {
+    if name
+        .strip_prefix('_')
+        .is_some_and(|index| index.parse::<usize>().is_ok())
+    {
+        return Err(format!(
+            "`{}` is a previous result and can't be assigned",
+            name
+        ));
+    }
+    match name.parse::<Ast>() {
+        Ok(Ast::Var(_)) => Ok(()),
+        _ => Err(format!("`{}` is a reserved word", name)),
+    }
+}
+
+fn parse(source: &str) -> Result<Ast, String> {
+    source
+        .parse()
+        .map_err(|error: ParseError| positioned(source, &error))
+}
+
+fn positioned(source: &str, error: &ParseError) -> String {
+    format!(
+        "{}\n  {}\n  {}^",
+        error,
+        source,
+        " ".repeat(error.position())
+    )
+}
+
+fn dump(ast: &Ast, depth: usize, tree: &mut String) {
+    let indent = "  ".repeat(depth);
+    match ast {
+        Ast::Num(num) => tree.push_str(&format!("{}Num {}\n", indent, num)),
+        Ast::Var(name) => tree.push_str(&format!("{}Var {}\n", indent, name)),
+        Ast::Neg(operand) => {
+            tree.push_str(&format!("{}Neg\n", indent));
+            dump(operand, depth + 1, tree);
+        }
+        Ast::Op(op, left, right) => {
+            tree.push_str(&format!("{}Op {}\n", indent, op));
+            dump(left, depth + 1, tree);
+            dump(right, depth + 1, tree);
+        }
+        Ast::Call(func, arg) => {
+            tree.push_str(&format!("{}Call {}\n", indent, func));
+            dump(arg, depth + 1, tree);
+        }
+        Ast::Bool(value) => tree.push_str(&format!("{}Bool {}\n", indent, value)),
+        Ast::Not(operand) => {
+            tree.push_str(&format!("{}Not\n", indent));
+            dump(operand, depth + 1, tree);
+        }
+        Ast::Cmp(comparison, left, right) => {
+            tree.push_str(&format!("{}Cmp {}\n", indent, comparison));
+            dump(left, depth + 1, tree);
+            dump(right, depth + 1, tree);
+        }
+        Ast::And(left, right) | Ast::Or(left, right) => {
+            let name = if let Ast::And(_, _) = ast {
+                "And"
+            } else {
+                "Or"
+            };
+            tree.push_str(&format!("{}{}\n", indent, name));
+            dump(left, depth + 1, tree);
+            dump(right, depth + 1, tree);
+        }
+        Ast::If(condition, then, otherwise) => {
+            tree.push_str(&format!("{}If\n", indent));
+            dump(condition, depth + 1, tree);
+            dump(then, depth + 1, tree);
+            dump(otherwise, depth + 1, tree);
+        }
+        Ast::Convert(operand, unit) => {
+            tree.push_str(&format!("{}Convert {}\n", indent, unit));
+            dump(operand, depth + 1, tree);
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let interactive = io::stdin().is_terminal();
+    let mut session = Session::default();
+    let mut stdout = io::stdout();
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if interactive {
+            write!(stdout, "> ")?;
+            stdout.flush()?;
+        }
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        if line.trim() == ":quit" {
+            break;
+        }
+        match session.execute(&line) {
+            Ok(Some(output)) => writeln!(stdout, "{}", output)?,
+            Ok(None) => {}
+            Err(error) => eprintln!("error: {}", error),
+        }
+    }
+    Ok(())
+}
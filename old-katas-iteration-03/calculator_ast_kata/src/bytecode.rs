@@ -0,0 +1,404 @@
+use crate::ast::{Ast, Comparison, EvalError, Func, Type};
+use crate::number::Number;
+use stack::Stack;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Const(f64),
+    Load(usize),
+    Neg,
+    Op(char),
+    Call(Func),
+    Cmp(Comparison),
+    Not,
+    Jump(usize),
+    JumpUnless(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    code: Vec<Instruction>,
+    vars: Vec<String>,
+}
+
+impl Program {
+    pub fn code(&self) -> &[Instruction] {
+        &self.code
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.vars
+    }
+
+    pub fn bind(&self, vars: &HashMap<String, f64>) -> Result<Vec<f64>, EvalError> {
+        self.vars
+            .iter()
+            .map(|name| {
+                vars.get(name)
+                    .copied()
+                    .ok_or_else(|| EvalError::UnknownVariable(name.clone()))
+            })
+            .collect()
+    }
+
+    fn emit(&mut self, ast: &Ast) -> Result<(), EvalError> {
+        match ast {
+            Ast::Num(num) => self.code.push(Instruction::Const(*num)),
+            Ast::Var(name) => {
+                let slot = match self.vars.iter().position(|var| var == name) {
+                    Some(slot) => slot,
+                    None => {
+                        self.vars.push(name.clone());
+                        self.vars.len() - 1
+                    }
+                };
+                self.code.push(Instruction::Load(slot));
+            }
+            Ast::Neg(operand) => {
+                self.emit(operand)?;
+                self.code.push(Instruction::Neg);
+            }
+            Ast::Op(op, left, right) => {
+                self.emit(left)?;
+                self.emit(right)?;
+                self.code.push(Instruction::Op(*op));
+            }
+            Ast::Call(func, arg) => {
+                self.emit(arg)?;
+                self.code.push(Instruction::Call(*func));
+            }
+            Ast::Bool(value) => self.code.push(Instruction::Const(truth(*value))),
+            Ast::Not(operand) => {
+                self.emit(operand)?;
+                self.code.push(Instruction::Not);
+            }
+            Ast::Cmp(comparison, left, right) => {
+                self.emit(left)?;
+                self.emit(right)?;
+                self.code.push(Instruction::Cmp(*comparison));
+            }
+            Ast::And(left, right) => self.emit_branches(left, right, &Ast::Bool(false))?,
+            Ast::Or(left, right) => self.emit_branches(left, &Ast::Bool(true), right)?,
+            Ast::If(condition, then, otherwise) => {
+                self.emit_branches(condition, then, otherwise)?
+            }
+            Ast::Convert(_, _) => return Err(EvalError::NotRepresentable(ast.to_string())),
+        }
+        Ok(())
+    }
+
+    fn emit_branches(
+        &mut self,
+        condition: &Ast,
+        then: &Ast,
+        otherwise: &Ast,
+    ) -> Result<(), EvalError> {
+        self.emit(condition)?;
+        let jump_to_otherwise = self.code.len();
+        self.code.push(Instruction::JumpUnless(0));
+        self.emit(then)?;
+        let jump_to_end = self.code.len();
+        self.code.push(Instruction::Jump(0));
+        self.code[jump_to_otherwise] = Instruction::JumpUnless(self.code.len());
+        self.emit(otherwise)?;
+        self.code[jump_to_end] = Instruction::Jump(self.code.len());
+        Ok(())
+    }
+}
+
+fn truth(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (index, instruction) in self.code.iter().enumerate() {
+            write!(f, "{:04} ", index)?;
+            match instruction {
+                Instruction::Const(num) => writeln!(f, "const {}", num)?,
+                Instruction::Load(slot) => writeln!(f, "load {} ({})", slot, self.vars[*slot])?,
+                Instruction::Neg => writeln!(f, "neg")?,
+                Instruction::Op(op) => writeln!(f, "op {}", op)?,
+                Instruction::Call(func) => writeln!(f, "call {}", func)?,
+                Instruction::Cmp(comparison) => writeln!(f, "cmp {}", comparison)?,
+                Instruction::Not => writeln!(f, "not")?,
+                Instruction::Jump(target) => writeln!(f, "jump {:04}", target)?,
+                Instruction::JumpUnless(target) => writeln!(f, "jump_unless {:04}", target)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Ast {
+    pub fn compile(&self) -> Result<Program, EvalError> {
+        match self.check()? {
+            Type::Number => {
+                let mut program = Program {
+                    code: vec![],
+                    vars: vec![],
+                };
+                program.emit(self)?;
+                Ok(program)
+            }
+            found => Err(EvalError::TypeMismatch {
+                expected: Type::Number,
+                found,
+            }),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Vm {
+    stack: Stack<f64>,
+}
+
+impl Vm {
+    pub fn run(&mut self, program: &Program, args: &[f64]) -> Result<f64, EvalError> {
+        if args.len() < program.vars.len() {
+            return Err(EvalError::UnknownVariable(program.vars[args.len()].clone()));
+        }
+        // a run that failed halfway leaves its operands behind
+        while self.stack.pop().is_some() {}
+        let mut top = None;
+        let mut counter = 0;
+        while let Some(instruction) = program.code.get(counter) {
+            counter += 1;
+            top = match *instruction {
+                Instruction::Const(num) => Some(self.spill(top, num)),
+                Instruction::Load(slot) => Some(self.spill(top, args[slot])),
+                Instruction::Neg => Some(-Self::top(top)),
+                Instruction::Op(op) => Some(self.pop().apply_op(op, &Self::top(top))?),
+                Instruction::Call(func) => Some(func.apply(Self::top(top))),
+                Instruction::Cmp(comparison) => Some(truth(
+                    comparison.test(self.pop().partial_cmp(&Self::top(top))),
+                )),
+                Instruction::Not => Some(truth(Self::top(top) == 0.0)),
+                Instruction::Jump(target) => {
+                    counter = target;
+                    top
+                }
+                Instruction::JumpUnless(target) => {
+                    if Self::top(top) == 0.0 {
+                        counter = target;
+                    }
+                    self.stack.pop()
+                }
+            };
+        }
+        Ok(Self::top(top))
+    }
+
+    // the top of the operand stack is kept in a local so only the operands
+    // waiting for a binary operation go through the linked `Stack`
+    fn spill(&mut self, top: Option<f64>, num: f64) -> f64 {
+        if let Some(top) = top {
+            self.stack.push(top);
+        }
+        num
+    }
+
+    fn top(top: Option<f64>) -> f64 {
+        top.expect("compiled program never underflows the operand stack")
+    }
+
+    fn pop(&mut self) -> f64 {
+        self.stack
+            .pop()
+            .expect("compiled program never underflows the operand stack")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiled(source: &str) -> Program {
+        source.parse::<Ast>().unwrap().compile().unwrap()
+    }
+
+    #[test]
+    fn compile_number() {
+        assert_eq!(compiled("5").code(), &[Instruction::Const(5.0)]);
+    }
+
+    #[test]
+    fn compile_operations_in_postfix_order() {
+        assert_eq!(
+            compiled("1+2*3").code(),
+            &[
+                Instruction::Const(1.0),
+                Instruction::Const(2.0),
+                Instruction::Const(3.0),
+                Instruction::Op('*'),
+                Instruction::Op('+'),
+            ]
+        );
+    }
+
+    #[test]
+    fn variables_share_slots() {
+        let program = compiled("x*y+x");
+
+        assert_eq!(program.variables(), &["x".to_owned(), "y".to_owned()]);
+        assert_eq!(
+            program.code(),
+            &[
+                Instruction::Load(0),
+                Instruction::Load(1),
+                Instruction::Op('*'),
+                Instruction::Load(0),
+                Instruction::Op('+'),
+            ]
+        );
+    }
+
+    #[test]
+    fn compile_negation_and_call() {
+        assert_eq!(
+            compiled("-sin(x)").code(),
+            &[
+                Instruction::Load(0),
+                Instruction::Call(Func::Sin),
+                Instruction::Neg
+            ]
+        );
+    }
+
+    #[test]
+    fn run_program() {
+        let mut vm = Vm::default();
+
+        assert_eq!(vm.run(&compiled("2^x-y/4"), &[3.0, 2.0]), Ok(7.5));
+        assert_eq!(vm.run(&compiled("2^x-y/4"), &[1.0, 4.0]), Ok(1.0));
+    }
+
+    #[test]
+    fn run_with_missing_argument() {
+        let mut vm = Vm::default();
+
+        assert_eq!(
+            vm.run(&compiled("x+y"), &[1.0]),
+            Err(EvalError::UnknownVariable("y".to_owned()))
+        );
+    }
+
+    #[test]
+    fn reuse_vm_after_an_error() {
+        let failing = Ast::op(
+            '+',
+            Ast::num(1.0),
+            Ast::op('%', Ast::num(2.0), Ast::num(3.0)),
+        )
+        .compile()
+        .unwrap();
+        let mut vm = Vm::default();
+
+        assert_eq!(vm.run(&failing, &[]), Err(EvalError::UnknownOperator('%')));
+        assert_eq!(vm.run(&compiled("x*2"), &[4.0]), Ok(8.0));
+        assert_eq!(vm.stack.pop(), None);
+    }
+
+    #[test]
+    fn bind_arguments_by_name() {
+        let program = compiled("b-a");
+        let vars = vec![("a".to_owned(), 1.0), ("b".to_owned(), 5.0)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(program.bind(&vars), Ok(vec![5.0, 1.0]));
+    }
+
+    #[test]
+    fn vm_agrees_with_tree_walking() {
+        let ast: Ast = "3*x^2 + 2*x*y - sin(y)/4 + sqrt(x) - -exp(y/x)"
+            .parse()
+            .unwrap();
+        let program = ast.compile().unwrap();
+        let mut vm = Vm::default();
+        for &(x, y) in &[(1.0, 2.0), (0.5, -3.0), (4.0, 0.0)] {
+            let vars = vec![("x".to_owned(), x), ("y".to_owned(), y)]
+                .into_iter()
+                .collect();
+
+            assert_eq!(
+                vm.run(&program, &program.bind(&vars).unwrap()),
+                ast.eval(&vars)
+            );
+        }
+    }
+
+    #[test]
+    fn compile_conditional_with_jumps() {
+        let program = compiled("x > 0 ? x : -x");
+
+        assert_eq!(
+            program.code(),
+            &[
+                Instruction::Load(0),
+                Instruction::Const(0.0),
+                Instruction::Cmp(Comparison::Gt),
+                Instruction::JumpUnless(6),
+                Instruction::Load(0),
+                Instruction::Jump(8),
+                Instruction::Load(0),
+                Instruction::Neg,
+            ]
+        );
+    }
+
+    #[test]
+    fn compile_rejects_ill_typed_programs() {
+        for source in &["x > 0", "x + (y < 1)"] {
+            assert!(matches!(
+                source.parse::<Ast>().unwrap().compile(),
+                Err(EvalError::TypeMismatch { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn unit_conversions_are_not_compiled() {
+        assert_eq!(
+            "x to km".parse::<Ast>().unwrap().compile(),
+            Err(EvalError::NotRepresentable("x to km".to_owned()))
+        );
+    }
+
+    #[test]
+    fn vm_agrees_with_tree_walking_on_conditions() {
+        let ast: Ast = "(x > y && !(x == 2) || y <= -3 ? x * 2 : y) + (x != y ? 1 : 0)"
+            .parse()
+            .unwrap();
+        let program = ast.compile().unwrap();
+        let mut vm = Vm::default();
+        for &(x, y) in &[(1.0, 2.0), (2.0, 1.0), (3.0, 1.0), (0.5, -3.0), (4.0, 4.0)] {
+            let vars = vec![("x".to_owned(), x), ("y".to_owned(), y)]
+                .into_iter()
+                .collect();
+
+            assert_eq!(
+                vm.run(&program, &program.bind(&vars).unwrap()),
+                ast.eval(&vars),
+                "x = {}, y = {}",
+                x,
+                y
+            );
+        }
+    }
+
+    #[test]
+    fn display_program() {
+        assert_eq!(
+            compiled("-x*2").to_string(),
+            "0000 load 0 (x)\n0001 neg\n0002 const 2\n0003 op *\n"
+        );
+    }
+}
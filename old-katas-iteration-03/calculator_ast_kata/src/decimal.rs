@@ -0,0 +1,330 @@
+use crate::ast::{Comparison, EvalError, Func};
+use crate::number::Number;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+// 10^38 is the largest power of ten an i128 mantissa holds
+const MAX_SCALE: u32 = 38;
+
+/// A fixed-point number with `SCALE` digits after the point, at most 38 of them.
+///
+/// ```compile_fail
+/// let _ = "1".parse::<calculator_ast_kata::Decimal<39>>();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Decimal<const SCALE: u32> {
+    mantissa: i128,
+}
+
+impl<const SCALE: u32> Decimal<SCALE> {
+    const UNIT: i128 = {
+        assert!(
+            SCALE <= MAX_SCALE,
+            "Decimal supports at most 38 fraction digits"
+        );
+        10i128.pow(SCALE)
+    };
+
+    pub fn from_mantissa(mantissa: i128) -> Self {
+        let _ = Self::UNIT;
+        Decimal { mantissa }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 / Self::UNIT as f64
+    }
+
+    pub fn from_f64(value: f64) -> Result<Self, EvalError> {
+        let scaled = (value * Self::UNIT as f64).round();
+        if !scaled.is_finite() || scaled.abs() >= i128::MAX as f64 {
+            return Err(EvalError::NotRepresentable(value.to_string()));
+        }
+        Ok(Decimal {
+            mantissa: scaled as i128,
+        })
+    }
+
+    fn is_integer(&self) -> bool {
+        self.mantissa % Self::UNIT == 0
+    }
+
+    fn pow(&self, exponent: &Self) -> Result<Self, EvalError> {
+        if !exponent.is_integer() {
+            return Self::from_f64(self.to_f64().powf(exponent.to_f64()));
+        }
+        let power = exponent.mantissa / Self::UNIT;
+        let one = Decimal {
+            mantissa: Self::UNIT,
+        };
+        let (mut result, mut base, mut remaining) = (one, *self, power.unsigned_abs());
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = result.apply_op('*', &base)?;
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                base = base.apply_op('*', &base)?;
+            }
+        }
+        if power < 0 {
+            one.apply_op('/', &result)
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+// rounds half away from zero, the way money amounts are usually rounded
+fn divide_rounded(dividend: i128, divisor: i128) -> i128 {
+    let quotient = dividend / divisor;
+    let remainder = dividend % divisor;
+    if remainder.abs() * 2 >= divisor.abs() {
+        if (dividend < 0) == (divisor < 0) {
+            quotient + 1
+        } else {
+            quotient - 1
+        }
+    } else {
+        quotient
+    }
+}
+
+impl<const SCALE: u32> Number for Decimal<SCALE> {
+    fn negate(&self) -> Result<Self, EvalError> {
+        self.mantissa
+            .checked_neg()
+            .map(|mantissa| Decimal { mantissa })
+            .ok_or(EvalError::Overflow)
+    }
+
+    fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    fn apply_op(&self, op: char, other: &Self) -> Result<Self, EvalError> {
+        let (left, right) = (self.mantissa, other.mantissa);
+        let mantissa = match op {
+            '+' => left.checked_add(right),
+            '-' => left.checked_sub(right),
+            '*' => left
+                .checked_mul(right)
+                .map(|product| divide_rounded(product, Self::UNIT)),
+            '/' if right == 0 => return Err(EvalError::DivisionByZero),
+            '/' => left
+                .checked_mul(Self::UNIT)
+                .map(|dividend| divide_rounded(dividend, right)),
+            '^' => return self.pow(other),
+            _ => return Err(EvalError::UnknownOperator(op)),
+        };
+        mantissa
+            .map(|mantissa| Decimal { mantissa })
+            .ok_or(EvalError::Overflow)
+    }
+
+    fn apply_func(&self, func: Func) -> Result<Self, EvalError> {
+        Self::from_f64(func.apply(self.to_f64()))
+    }
+    fn compare(&self, comparison: Comparison, other: &Self) -> Result<bool, EvalError> {
+        Ok(comparison.test(Some(self.cmp(other))))
+    }
+}
+
+impl<const SCALE: u32> FromStr for Decimal<SCALE> {
+    type Err = ();
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let (integer, fraction) = match source.find('.') {
+            Some(dot) => (&source[..dot], &source[dot + 1..]),
+            None => (source, ""),
+        };
+        if (integer.is_empty() && fraction.is_empty())
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(());
+        }
+        let scale = SCALE as usize;
+        // digits past the first one beyond SCALE can't change which way it rounds
+        let (kept, rounding) = match fraction.as_bytes().get(scale) {
+            Some(&next) => (&fraction[..scale], next),
+            None => (fraction, b'0'),
+        };
+        let integer: i128 = match integer {
+            "" => 0,
+            integer => integer.parse().map_err(|_| ())?,
+        };
+        let fraction: i128 = match kept {
+            "" => 0,
+            kept => kept.parse::<i128>().map_err(|_| ())? * 10i128.pow(SCALE - kept.len() as u32),
+        };
+        let mantissa = integer
+            .checked_mul(Self::UNIT)
+            .and_then(|mantissa| mantissa.checked_add(fraction))
+            .and_then(|mantissa| mantissa.checked_add(i128::from(rounding >= b'5')))
+            .ok_or(())?;
+        Ok(Decimal { mantissa })
+    }
+}
+
+impl<const SCALE: u32> Display for Decimal<SCALE> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let integer = (self.mantissa / Self::UNIT).unsigned_abs();
+        let fraction = (self.mantissa % Self::UNIT).unsigned_abs();
+        if SCALE == 0 {
+            write!(f, "{}{}", sign, integer)
+        } else {
+            write!(
+                f,
+                "{}{}.{:0width$}",
+                sign,
+                integer,
+                fraction,
+                width = SCALE as usize
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Ast;
+    use std::collections::HashMap;
+
+    type Cents = Decimal<2>;
+
+    fn evaluated<const SCALE: u32>(source: &str) -> Result<String, EvalError> {
+        source
+            .parse::<Ast<Decimal<SCALE>>>()
+            .unwrap()
+            .eval(&HashMap::new())
+            .map(|decimal| decimal.to_string())
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(
+            "12.5".parse::<Cents>().map(|d| d.to_string()),
+            Ok("12.50".to_owned())
+        );
+        assert_eq!(
+            "0.005".parse::<Cents>().map(|d| d.to_string()),
+            Ok("0.01".to_owned())
+        );
+        assert_eq!(
+            "7".parse::<Decimal<0>>().map(|d| d.to_string()),
+            Ok("7".to_owned())
+        );
+    }
+
+    #[test]
+    fn exact_addition() {
+        assert_eq!(evaluated::<2>("0.1 + 0.2"), Ok("0.30".to_owned()));
+    }
+
+    #[test]
+    fn negative_values() {
+        assert_eq!(evaluated::<2>("0.1 - 0.35"), Ok("-0.25".to_owned()));
+    }
+
+    #[test]
+    fn multiplication_rounds_to_scale() {
+        assert_eq!(evaluated::<2>("19.99 * 0.15"), Ok("3.00".to_owned()));
+        assert_eq!(evaluated::<4>("19.99 * 0.15"), Ok("2.9985".to_owned()));
+    }
+
+    #[test]
+    fn division_rounds_to_scale() {
+        assert_eq!(evaluated::<4>("1 / 3"), Ok("0.3333".to_owned()));
+        assert_eq!(evaluated::<4>("2 / 3"), Ok("0.6667".to_owned()));
+        assert_eq!(evaluated::<4>("-2 / 3"), Ok("-0.6667".to_owned()));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(evaluated::<2>("1 / 0"), Err(EvalError::DivisionByZero));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(evaluated::<10>("10^20 * 10^20"), Err(EvalError::Overflow));
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(evaluated::<3>("1.1^2"), Ok("1.210".to_owned()));
+        assert_eq!(evaluated::<3>("2^-2"), Ok("0.250".to_owned()));
+        assert_eq!(evaluated::<3>("4^0.5"), Ok("2.000".to_owned()));
+    }
+
+    #[test]
+    fn parse_rounds_away_digits_past_i128() {
+        let tiny = format!("0.{}1", "0".repeat(47));
+        let half = format!("0.{}5", "0".repeat(2));
+
+        assert_eq!(tiny.parse::<Cents>(), Ok(Cents::from_mantissa(0)));
+        assert_eq!(
+            format!("{}.{}", 1, "9".repeat(37)).parse::<Cents>(),
+            Ok(Cents::from_mantissa(200))
+        );
+        assert_eq!(half.parse::<Cents>(), Ok(Cents::from_mantissa(1)));
+        assert_eq!(
+            evaluated::<2>(&format!("{} + 1", tiny)),
+            Ok("1.00".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_rounds_literals_wider_than_i128() {
+        assert_eq!(
+            format!("1.{}", "9".repeat(41)).parse::<Cents>(),
+            Ok(Cents::from_mantissa(200))
+        );
+        assert_eq!(
+            "12345678901234567890.1234567890123456789012".parse::<Cents>(),
+            Ok(Cents::from_mantissa(1_234_567_890_123_456_789_012))
+        );
+        assert_eq!(
+            "12345678901234567890.1234567890123456789012".parse::<Decimal<18>>(),
+            Ok(Decimal::from_mantissa(
+                12_345_678_901_234_567_890_123_456_789_012_345_679
+            ))
+        );
+        assert_eq!("0.125".parse::<Cents>(), Ok(Cents::from_mantissa(13)));
+        assert_eq!("0.124999".parse::<Cents>(), Ok(Cents::from_mantissa(12)));
+        assert_eq!(format!("{}.5", "9".repeat(40)).parse::<Cents>(), Err(()));
+    }
+
+    #[test]
+    fn smallest_mantissa() {
+        let min = Decimal::<2>::from_mantissa(i128::MIN);
+
+        assert_eq!(min.negate(), Err(EvalError::Overflow));
+        assert_eq!(min.to_string(), "-1701411834604692317316873037158841057.28");
+    }
+
+    #[test]
+    fn widest_scale() {
+        assert_eq!(
+            "1".parse::<Decimal<38>>(),
+            Ok(Decimal::from_mantissa(10i128.pow(38)))
+        );
+        assert_eq!(
+            "0.5".parse::<Decimal<38>>().map(|d| d.to_string()),
+            Ok(format!("0.5{}", "0".repeat(37)))
+        );
+        assert_eq!("2".parse::<Decimal<38>>(), Err(()));
+    }
+
+    #[test]
+    fn functions_are_rounded_to_scale() {
+        assert_eq!(evaluated::<3>("sqrt(2)"), Ok("1.414".to_owned()));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::ast::{Ast, EvalError, Func};
+
+impl Ast {
+    /// The derivative by `var`, simplified.
+    ///
+    /// # Panics
+    ///
+    /// On boolean expressions and unit conversions, which have no derivative;
+    /// `try_derive` reports those as `EvalError::NotRepresentable` instead.
+    pub fn derive(&self, var: &str) -> Ast {
+        match self.try_derive(var) {
+            Ok(derivative) => derivative,
+            Err(error) => panic!("can't differentiate {}: {:?}", self, error),
+        }
+    }
+
+    // boolean expressions and unit conversions have no derivative
+    pub fn try_derive(&self, var: &str) -> Result<Ast, EvalError> {
+        self.differentiate(var)
+            .map(|derivative| derivative.simplify())
+    }
+
+    fn differentiate(&self, var: &str) -> Result<Ast, EvalError> {
+        Ok(match self {
+            Ast::Num(_) => Ast::Num(0.0),
+            Ast::Var(name) if name == var => Ast::Num(1.0),
+            Ast::Var(_) => Ast::Num(0.0),
+            Ast::Neg(operand) => Ast::negate(operand.differentiate(var)?),
+            Ast::Op(op @ '+', left, right) | Ast::Op(op @ '-', left, right) => {
+                Ast::op(*op, left.differentiate(var)?, right.differentiate(var)?)
+            }
+            Ast::Op('*', left, right) => Ast::op(
+                '+',
+                Ast::op('*', left.differentiate(var)?, (**right).clone()),
+                Ast::op('*', (**left).clone(), right.differentiate(var)?),
+            ),
+            Ast::Op('/', left, right) => Ast::op(
+                '/',
+                Ast::op(
+                    '-',
+                    Ast::op('*', left.differentiate(var)?, (**right).clone()),
+                    Ast::op('*', (**left).clone(), right.differentiate(var)?),
+                ),
+                Ast::op('^', (**right).clone(), Ast::Num(2.0)),
+            ),
+            Ast::Op('^', base, exponent) => differentiate_power(base, exponent, var)?,
+            Ast::Op(op, _, _) => return Err(EvalError::UnknownOperator(*op)),
+            Ast::Call(func, arg) => {
+                Ast::op('*', differentiate_call(*func, arg), arg.differentiate(var)?)
+            }
+            Ast::If(condition, then, otherwise) => Ast::conditional(
+                (**condition).clone(),
+                then.differentiate(var)?,
+                otherwise.differentiate(var)?,
+            ),
+            Ast::Bool(_)
+            | Ast::Not(_)
+            | Ast::Cmp(_, _, _)
+            | Ast::And(_, _)
+            | Ast::Or(_, _)
+            | Ast::Convert(_, _) => return Err(EvalError::NotRepresentable(self.to_string())),
+        })
+    }
+
+    fn depends_on(&self, var: &str) -> bool {
+        self.variables().contains(&var)
+    }
+}
+
+fn differentiate_power(base: &Ast, exponent: &Ast, var: &str) -> Result<Ast, EvalError> {
+    let power = Ast::op('^', base.clone(), exponent.clone());
+    Ok(match (base.depends_on(var), exponent.depends_on(var)) {
+        (false, false) => Ast::Num(0.0),
+        (true, false) => Ast::op(
+            '*',
+            Ast::op(
+                '*',
+                exponent.clone(),
+                Ast::op(
+                    '^',
+                    base.clone(),
+                    Ast::op('-', exponent.clone(), Ast::Num(1.0)),
+                ),
+            ),
+            base.differentiate(var)?,
+        ),
+        (false, true) => Ast::op(
+            '*',
+            Ast::op('*', power, Ast::call(Func::Ln, base.clone())),
+            exponent.differentiate(var)?,
+        ),
+        (true, true) => Ast::op(
+            '*',
+            power,
+            Ast::op(
+                '+',
+                Ast::op(
+                    '*',
+                    exponent.differentiate(var)?,
+                    Ast::call(Func::Ln, base.clone()),
+                ),
+                Ast::op(
+                    '/',
+                    Ast::op('*', exponent.clone(), base.differentiate(var)?),
+                    base.clone(),
+                ),
+            ),
+        ),
+    })
+}
+
+fn differentiate_call(func: Func, arg: &Ast) -> Ast {
+    let arg = arg.clone();
+    match func {
+        Func::Sin => Ast::call(Func::Cos, arg),
+        Func::Cos => Ast::negate(Ast::call(Func::Sin, arg)),
+        Func::Tan => Ast::op(
+            '/',
+            Ast::Num(1.0),
+            Ast::op('^', Ast::call(Func::Cos, arg), Ast::Num(2.0)),
+        ),
+        Func::Exp => Ast::call(Func::Exp, arg),
+        Func::Ln => Ast::op('/', Ast::Num(1.0), arg),
+        Func::Sqrt => Ast::op(
+            '/',
+            Ast::Num(1.0),
+            Ast::op('*', Ast::Num(2.0), Ast::call(Func::Sqrt, arg)),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn derived(source: &str, var: &str) -> String {
+        source.parse::<Ast>().unwrap().derive(var).to_string()
+    }
+
+    fn at(name: &str, value: f64) -> HashMap<String, f64> {
+        vec![(name.to_owned(), value)].into_iter().collect()
+    }
+
+    #[test]
+    fn constant() {
+        assert_eq!(derived("42", "x"), "0");
+    }
+
+    #[test]
+    fn variable() {
+        assert_eq!(derived("x", "x"), "1");
+        assert_eq!(derived("y", "x"), "0");
+    }
+
+    #[test]
+    fn sum_rule() {
+        assert_eq!(derived("x + 3*x - y", "x"), "4");
+    }
+
+    #[test]
+    fn product_rule() {
+        assert_eq!(derived("x*y", "x"), "y");
+        assert_eq!(derived("x*x", "x"), "2 * x");
+    }
+
+    #[test]
+    fn quotient_rule() {
+        assert_eq!(derived("1/x", "x"), "-1 / x ^ 2");
+    }
+
+    #[test]
+    fn power_rule() {
+        assert_eq!(derived("x^3", "x"), "3 * x ^ 2");
+        assert_eq!(derived("2^x", "x"), "0.6931471805599453 * 2 ^ x");
+    }
+
+    #[test]
+    fn chain_rule() {
+        assert_eq!(derived("sin(2*x)", "x"), "2 * cos(2 * x)");
+        assert_eq!(derived("exp(x^2)", "x"), "2 * x * exp(x ^ 2)");
+        assert_eq!(derived("ln(y)", "x"), "0");
+    }
+
+    #[test]
+    fn partial_derivative() {
+        assert_eq!(derived("x^2*y + y^2", "y"), "2 * y + x ^ 2");
+    }
+
+    #[test]
+    fn piecewise_derivative() {
+        assert_eq!(derived("x > 0 ? x^2 : -x", "x"), "x > 0 ? 2 * x : -1");
+    }
+
+    #[test]
+    fn piecewise_with_a_boolean_branch() {
+        let ast: Ast = "x > 0 ? x > 1 : x".parse().unwrap();
+
+        assert_eq!(
+            ast.try_derive("x"),
+            Err(EvalError::NotRepresentable("x > 1".to_owned()))
+        );
+    }
+
+    #[test]
+    fn booleans_and_conversions_have_no_derivative() {
+        for source in &["x > 1", "!(x < 2) || true", "x to km"] {
+            let ast: Ast = source.parse().unwrap();
+
+            assert_eq!(
+                ast.try_derive("x"),
+                Err(EvalError::NotRepresentable(ast.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "can't differentiate x > 1")]
+    fn derive_panics_on_booleans() {
+        derived("x > 1", "x");
+    }
+
+    #[test]
+    fn derivatives_of_functions_match_numeric_slope() {
+        let h = 1e-6;
+        for source in &[
+            "sin(x)",
+            "cos(x)",
+            "tan(x)",
+            "exp(x)",
+            "ln(x)",
+            "sqrt(x)",
+            "x^x",
+            "(x+1)/(x-3)",
+        ] {
+            let ast: Ast = source.parse().unwrap();
+            let derivative = ast.derive("x");
+            let x = 0.7;
+            let slope = (ast.eval(&at("x", x + h)).unwrap() - ast.eval(&at("x", x - h)).unwrap())
+                / (2.0 * h);
+
+            assert!(
+                (derivative.eval(&at("x", x)).unwrap() - slope).abs() < 1e-6,
+                "d/dx {}",
+                source
+            );
+        }
+    }
+}
@@ -0,0 +1,507 @@
+use crate::ast::{Comparison, EvalError, Func};
+use crate::number::Number;
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+// widens the search for extremums of periodic functions, so that rounding
+// in `x / 2π` can only loosen an enclosure but never lose an extremum
+const SLACK: f64 = 1e-9;
+const MANTISSA_LIMIT: u128 = 1 << 53;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    pub fn new(lo: f64, hi: f64) -> Self {
+        assert!(
+            lo <= hi,
+            "interval bounds [{}, {}] are out of order",
+            lo,
+            hi
+        );
+        Interval { lo, hi }
+    }
+
+    pub fn point(value: f64) -> Self {
+        Interval::new(value, value)
+    }
+
+    pub fn entire() -> Self {
+        Interval::new(f64::NEG_INFINITY, f64::INFINITY)
+    }
+
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.lo <= value && value <= self.hi
+    }
+
+    fn is_point(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    // every operation rounds to nearest, so one ulp outwards on both sides
+    // is enough for the result to stay an enclosure
+    fn outward(lo: f64, hi: f64) -> Self {
+        if lo.is_nan() || hi.is_nan() {
+            return Interval::entire();
+        }
+        Interval::new(lo.next_down(), hi.next_up())
+    }
+
+    fn hull(bounds: [f64; 4]) -> Self {
+        if bounds.iter().any(|bound| bound.is_nan()) {
+            return Interval::entire();
+        }
+        let lo = bounds.iter().cloned().fold(f64::INFINITY, f64::min);
+        let hi = bounds.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        Interval::outward(lo, hi)
+    }
+
+    fn multiply(&self, other: &Self) -> Self {
+        let (a, b, c, d) = (self.lo, self.hi, other.lo, other.hi);
+        Interval::hull([product(a, c), product(a, d), product(b, c), product(b, d)])
+    }
+
+    fn divide(&self, divisor: &Self) -> Result<Self, EvalError> {
+        let (a, b, c, d) = (self.lo, self.hi, divisor.lo, divisor.hi);
+        if c > 0.0 || d < 0.0 {
+            return Ok(Interval::hull([a / c, a / d, b / c, b / d]));
+        }
+        if c == 0.0 && d == 0.0 {
+            return Err(EvalError::DivisionByZero);
+        }
+        if a == 0.0 && b == 0.0 {
+            return Ok(*self);
+        }
+        let quotient = match (c == 0.0, d == 0.0) {
+            (true, _) if b <= 0.0 => Interval::outward(f64::NEG_INFINITY, b / d),
+            (true, _) if a >= 0.0 => Interval::outward(a / d, f64::INFINITY),
+            (_, true) if b <= 0.0 => Interval::outward(b / c, f64::INFINITY),
+            (_, true) if a >= 0.0 => Interval::outward(f64::NEG_INFINITY, a / c),
+            _ => Interval::entire(),
+        };
+        Ok(quotient)
+    }
+
+    fn pow(&self, exponent: &Self) -> Result<Self, EvalError> {
+        let power = exponent.lo;
+        if exponent.is_point() && power.fract() == 0.0 && power.abs() <= i32::MAX as f64 {
+            return self.powi(power as i32);
+        }
+        if self.lo < 0.0 {
+            return Err(EvalError::NotRepresentable(format!(
+                "{} ^ {}",
+                self, exponent
+            )));
+        }
+        exponent
+            .multiply(&self.apply_func(Func::Ln)?)
+            .apply_func(Func::Exp)
+    }
+
+    fn powi(&self, power: i32) -> Result<Self, EvalError> {
+        if power < 0 {
+            return Interval::point(1.0).divide(&self.powi(-power)?);
+        }
+        let (lo, hi) = (self.lo.powi(power), self.hi.powi(power));
+        let result = if power == 0 {
+            Interval::point(1.0)
+        } else if power % 2 == 1 || self.lo >= 0.0 {
+            Interval::outward(lo, hi)
+        } else if self.hi <= 0.0 {
+            Interval::outward(hi, lo)
+        } else {
+            Interval::new(0.0, lo.max(hi).next_up())
+        };
+        Ok(result)
+    }
+
+    fn monotonic(&self, func: Func) -> Self {
+        Interval::outward(func.apply(self.lo), func.apply(self.hi))
+    }
+
+    fn periodic(&self, func: Func, peak: f64, trough: f64) -> Self {
+        if self.width() >= 2.0 * PI {
+            return Interval::new(-1.0, 1.0);
+        }
+        let (from, to) = (func.apply(self.lo), func.apply(self.hi));
+        let lo = if self.reaches(trough, 2.0 * PI) {
+            -1.0
+        } else {
+            from.min(to).next_down().max(-1.0)
+        };
+        let hi = if self.reaches(peak, 2.0 * PI) {
+            1.0
+        } else {
+            from.max(to).next_up().min(1.0)
+        };
+        Interval::new(lo, hi)
+    }
+
+    fn reaches(&self, point: f64, period: f64) -> bool {
+        ((self.lo - point) / period - SLACK).ceil() <= ((self.hi - point) / period + SLACK).floor()
+    }
+}
+
+fn product(left: f64, right: f64) -> f64 {
+    // zero times an unbounded end is zero, not NaN, for interval bounds
+    if left == 0.0 || right == 0.0 {
+        0.0
+    } else {
+        left * right
+    }
+}
+
+fn decide(certainly: bool, impossible: bool) -> Option<bool> {
+    if certainly {
+        Some(true)
+    } else if impossible {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+impl Number for Interval {
+    fn negate(&self) -> Result<Self, EvalError> {
+        Ok(Interval::new(-self.hi, -self.lo))
+    }
+
+    fn is_negative(&self) -> bool {
+        self.is_point() && self.lo.is_sign_negative()
+    }
+
+    fn apply_op(&self, op: char, other: &Self) -> Result<Self, EvalError> {
+        match op {
+            '+' => Ok(Interval::outward(self.lo + other.lo, self.hi + other.hi)),
+            '-' => Ok(Interval::outward(self.lo - other.hi, self.hi - other.lo)),
+            '*' => Ok(self.multiply(other)),
+            '/' => self.divide(other),
+            '^' => self.pow(other),
+            _ => Err(EvalError::UnknownOperator(op)),
+        }
+    }
+
+    fn apply_func(&self, func: Func) -> Result<Self, EvalError> {
+        match func {
+            Func::Sin => Ok(self.periodic(func, FRAC_PI_2, -FRAC_PI_2)),
+            Func::Cos => Ok(self.periodic(func, 0.0, PI)),
+            Func::Tan if self.width() >= PI || self.reaches(FRAC_PI_2, PI) => {
+                Ok(Interval::entire())
+            }
+            Func::Exp | Func::Tan => Ok(self.monotonic(func)),
+            Func::Ln | Func::Sqrt if self.lo < 0.0 => {
+                Err(EvalError::NotRepresentable(format!("{}({})", func, self)))
+            }
+            Func::Ln => Ok(self.monotonic(func)),
+            Func::Sqrt => {
+                let root = self.monotonic(func);
+                Ok(Interval::new(root.lo.max(0.0), root.hi))
+            }
+        }
+    }
+
+    fn compare(&self, comparison: Comparison, other: &Self) -> Result<bool, EvalError> {
+        let disjoint = self.hi < other.lo || self.lo > other.hi;
+        let same_point = self.is_point() && self == other;
+        let decided = match comparison {
+            Comparison::Eq => decide(same_point, disjoint),
+            Comparison::Ne => decide(disjoint, same_point),
+            Comparison::Lt => decide(self.hi < other.lo, self.lo >= other.hi),
+            Comparison::Le => decide(self.hi <= other.lo, self.lo > other.hi),
+            Comparison::Gt => decide(self.lo > other.hi, self.hi <= other.lo),
+            Comparison::Ge => decide(self.lo >= other.hi, self.hi < other.lo),
+        };
+        decided
+            .ok_or_else(|| EvalError::Indeterminate(format!("{} {} {}", self, comparison, other)))
+    }
+}
+
+fn parse_literal(source: &str) -> Result<Interval, ()> {
+    let source = source.trim();
+    let value: f64 = source.parse().map_err(|_| ())?;
+    if is_exact(source.trim_start_matches('-')) {
+        Ok(Interval::point(value))
+    } else {
+        Ok(Interval::outward(value, value))
+    }
+}
+
+// a decimal `digits / 10^n` is a binary fraction only when `5^n` divides the digits
+fn is_exact(literal: &str) -> bool {
+    let (integer, fraction) = match literal.find('.') {
+        Some(dot) => (&literal[..dot], literal[dot + 1..].trim_end_matches('0')),
+        None => (literal, ""),
+    };
+    let digits = format!("{}{}", integer, fraction).parse::<u128>();
+    let scale = 5u128.checked_pow(fraction.len() as u32);
+    match (digits, scale) {
+        (Ok(digits), Some(scale)) => digits % scale == 0 && digits / scale <= MANTISSA_LIMIT,
+        _ => false,
+    }
+}
+
+impl FromStr for Interval {
+    type Err = ();
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source
+            .strip_prefix('[')
+            .and_then(|bounds| bounds.strip_suffix(']'))
+        {
+            Some(bounds) => {
+                let comma = bounds.find(',').ok_or(())?;
+                let lo = parse_literal(&bounds[..comma])?.lo;
+                let hi = parse_literal(&bounds[comma + 1..])?.hi;
+                if lo <= hi {
+                    Ok(Interval::new(lo, hi))
+                } else {
+                    Err(())
+                }
+            }
+            None => parse_literal(source),
+        }
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_point() {
+            write!(f, "{}", self.lo)
+        } else {
+            write!(f, "[{}, {}]", self.lo, self.hi)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Ast;
+    use std::collections::HashMap;
+
+    fn evaluated(source: &str, bindings: &[(&str, f64, f64)]) -> Result<Interval, EvalError> {
+        let vars = bindings
+            .iter()
+            .map(|&(name, lo, hi)| (name.to_owned(), Interval::new(lo, hi)))
+            .collect();
+        source.parse::<Ast<Interval>>().unwrap().eval(&vars)
+    }
+
+    fn close(bound: f64, expected: f64) -> bool {
+        bound == expected || (bound - expected).abs() < 1e-9
+    }
+
+    fn assert_encloses(interval: Interval, lo: f64, hi: f64) {
+        assert!(
+            interval.contains(lo) && interval.contains(hi),
+            "{} does not enclose [{}, {}]",
+            interval,
+            lo,
+            hi
+        );
+        assert!(
+            close(interval.lo, lo) && close(interval.hi, hi),
+            "{} is not tight around [{}, {}]",
+            interval,
+            lo,
+            hi
+        );
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!("4".parse::<Interval>().unwrap().to_string(), "4");
+        assert_eq!(
+            "[-1, 2.5]".parse::<Interval>().unwrap().to_string(),
+            "[-1, 2.5]"
+        );
+        assert_eq!(
+            "[0.1, 0.2]".parse::<Interval>().unwrap().to_string(),
+            format!("[{}, {}]", 0.1f64.next_down(), 0.2f64.next_up())
+        );
+        assert_eq!("[2, 1]".parse::<Interval>(), Err(()));
+    }
+
+    #[test]
+    fn inexact_literals_are_enclosed() {
+        let tenth = "0.1".parse::<Interval>().unwrap();
+
+        assert!(tenth.lo() < 0.1 && 0.1 < tenth.hi());
+        assert_encloses(evaluated("0.1 + 0.2", &[]).unwrap(), 0.3, 0.3);
+    }
+
+    #[test]
+    fn addition_and_subtraction() {
+        let bindings = [("x", 1.0, 2.0), ("y", 3.0, 4.0)];
+
+        assert_encloses(evaluated("x + y", &bindings).unwrap(), 4.0, 6.0);
+        assert_encloses(evaluated("x - y", &bindings).unwrap(), -3.0, -1.0);
+        assert_encloses(evaluated("x - x", &bindings).unwrap(), -1.0, 1.0);
+    }
+
+    #[test]
+    fn multiplication_across_signs() {
+        let bindings = [("x", -2.0, 3.0), ("y", -5.0, -4.0)];
+
+        assert_encloses(evaluated("x * y", &bindings).unwrap(), -15.0, 10.0);
+        assert_encloses(evaluated("y * y", &bindings).unwrap(), 16.0, 25.0);
+    }
+
+    #[test]
+    fn division_by_interval_without_zero() {
+        assert_encloses(
+            evaluated("x / y", &[("x", 1.0, 2.0), ("y", -4.0, -2.0)]).unwrap(),
+            -1.0,
+            -0.25,
+        );
+    }
+
+    #[test]
+    fn division_by_interval_containing_zero() {
+        let positive = [("x", 1.0, 2.0)];
+
+        assert_eq!(
+            evaluated("x / y", &[positive[0], ("y", -1.0, 1.0)]),
+            Ok(Interval::entire())
+        );
+        assert_encloses(
+            evaluated("x / y", &[positive[0], ("y", 0.0, 4.0)]).unwrap(),
+            0.25,
+            f64::INFINITY,
+        );
+        assert_encloses(
+            evaluated("-x / y", &[positive[0], ("y", 0.0, 4.0)]).unwrap(),
+            f64::NEG_INFINITY,
+            -0.25,
+        );
+        assert_encloses(
+            evaluated("x / y", &[positive[0], ("y", -4.0, 0.0)]).unwrap(),
+            f64::NEG_INFINITY,
+            -0.25,
+        );
+        assert_eq!(
+            evaluated("x / 0", &positive),
+            Err(EvalError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn integer_powers() {
+        let bindings = [("x", -2.0, 3.0)];
+
+        assert_encloses(evaluated("x ^ 2", &bindings).unwrap(), 0.0, 9.0);
+        assert_encloses(evaluated("x ^ 3", &bindings).unwrap(), -8.0, 27.0);
+        assert_encloses(
+            evaluated("x ^ -2", &[("x", 2.0, 4.0)]).unwrap(),
+            0.0625,
+            0.25,
+        );
+    }
+
+    #[test]
+    fn real_powers_need_non_negative_base() {
+        assert_encloses(evaluated("x ^ 0.5", &[("x", 4.0, 9.0)]).unwrap(), 2.0, 3.0);
+        assert_eq!(
+            evaluated("x ^ 0.5", &[("x", -1.0, 1.0)]),
+            Err(EvalError::NotRepresentable("[-1, 1] ^ 0.5".to_owned()))
+        );
+    }
+
+    #[test]
+    fn monotonic_functions() {
+        assert_encloses(
+            evaluated("exp(x)", &[("x", 0.0, 1.0)]).unwrap(),
+            1.0,
+            1f64.exp(),
+        );
+        assert_encloses(
+            evaluated("ln(x)", &[("x", 1.0, 2.0)]).unwrap(),
+            0.0,
+            2f64.ln(),
+        );
+        assert_encloses(evaluated("sqrt(x)", &[("x", 0.0, 4.0)]).unwrap(), 0.0, 2.0);
+        assert_eq!(
+            evaluated("ln(x)", &[("x", -1.0, 1.0)]),
+            Err(EvalError::NotRepresentable("ln([-1, 1])".to_owned()))
+        );
+    }
+
+    #[test]
+    fn periodic_functions_reach_their_extremums() {
+        assert_encloses(evaluated("sin(x)", &[("x", 0.0, 3.0)]).unwrap(), 0.0, 1.0);
+        assert_encloses(
+            evaluated("cos(x)", &[("x", -1.0, 1.0)]).unwrap(),
+            1f64.cos(),
+            1.0,
+        );
+        assert_encloses(
+            evaluated("sin(x)", &[("x", 0.0, 100.0)]).unwrap(),
+            -1.0,
+            1.0,
+        );
+        assert_encloses(
+            evaluated("sin(x)", &[("x", 0.1, 0.2)]).unwrap(),
+            0.1f64.sin(),
+            0.2f64.sin(),
+        );
+    }
+
+    #[test]
+    fn tangent_across_a_pole() {
+        assert_eq!(
+            evaluated("tan(x)", &[("x", 1.0, 2.0)]),
+            Ok(Interval::entire())
+        );
+        assert_encloses(
+            evaluated("tan(x)", &[("x", -1.0, 1.0)]).unwrap(),
+            -1f64.tan(),
+            1f64.tan(),
+        );
+    }
+
+    #[test]
+    fn conditions_are_decided_only_when_certain() {
+        let bindings = [("x", 1.0, 2.0)];
+
+        assert_encloses(evaluated("x < 3 ? x : 0", &bindings).unwrap(), 1.0, 2.0);
+        assert_eq!(
+            evaluated("x > 1.5 ? x : 0", &bindings),
+            Err(EvalError::Indeterminate("[1, 2] > 1.5".to_owned()))
+        );
+    }
+
+    #[test]
+    fn result_encloses_every_sampled_scalar_evaluation() {
+        let source = "r1 * r2 / (r1 + r2) + sin(r1 / 50) - sqrt(r2) ^ 3";
+        let interval = evaluated(source, &[("r1", 99.0, 101.0), ("r2", 198.0, 202.0)]).unwrap();
+        let ast: Ast = source.parse().unwrap();
+        for step in 0..=20 {
+            for other in 0..=20 {
+                let vars: HashMap<String, f64> = vec![
+                    ("r1".to_owned(), 99.0 + step as f64 * 0.1),
+                    ("r2".to_owned(), 198.0 + other as f64 * 0.2),
+                ]
+                .into_iter()
+                .collect();
+
+                assert!(interval.contains(ast.eval(&vars).unwrap()));
+            }
+        }
+    }
+}
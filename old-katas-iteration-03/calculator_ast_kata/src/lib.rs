@@ -10,3 +10,27 @@ mod day_09;
 mod day_10;
 mod day_11;
 mod day_12;
+
+mod ast;
+mod big_int;
+mod bytecode;
+mod decimal;
+mod derive;
+mod interval;
+mod number;
+mod quantity;
+mod rational;
+mod simplify;
+mod unit;
+
+pub use crate::ast::{
+    Ast, Comparison, EvalError, Func, ParseError, ParseErrorKind, Type, Value,
+};
+pub use crate::big_int::BigInt;
+pub use crate::bytecode::{Instruction, Program, Vm};
+pub use crate::decimal::Decimal;
+pub use crate::interval::Interval;
+pub use crate::number::Number;
+pub use crate::quantity::Quantity;
+pub use crate::rational::Rational;
+pub use crate::unit::{Dimension, Unit};
@@ -0,0 +1,82 @@
+use crate::ast::{Comparison, EvalError, Func};
+use crate::unit::Unit;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+pub trait Number: Clone + Debug + PartialEq + Display + FromStr {
+    fn negate(&self) -> Result<Self, EvalError>;
+
+    fn is_negative(&self) -> bool;
+
+    fn apply_op(&self, op: char, other: &Self) -> Result<Self, EvalError>;
+
+    fn apply_func(&self, func: Func) -> Result<Self, EvalError>;
+
+    fn compare(&self, comparison: Comparison, other: &Self) -> Result<bool, EvalError>;
+
+    fn with_unit(&self, _unit: &Unit) -> Option<Self> {
+        None
+    }
+
+    fn convert(&self, unit: &Unit) -> Result<Self, EvalError> {
+        Err(EvalError::NotRepresentable(format!("{} to {}", self, unit)))
+    }
+}
+
+impl Number for f64 {
+    fn negate(&self) -> Result<Self, EvalError> {
+        Ok(-self)
+    }
+
+    fn is_negative(&self) -> bool {
+        self.is_sign_negative()
+    }
+
+    fn apply_op(&self, op: char, other: &Self) -> Result<Self, EvalError> {
+        match op {
+            '+' => Ok(self + other),
+            '-' => Ok(self - other),
+            '*' => Ok(self * other),
+            '/' => Ok(self / other),
+            '^' => Ok(self.powf(*other)),
+            _ => Err(EvalError::UnknownOperator(op)),
+        }
+    }
+
+    fn apply_func(&self, func: Func) -> Result<Self, EvalError> {
+        Ok(func.apply(*self))
+    }
+
+    fn compare(&self, comparison: Comparison, other: &Self) -> Result<bool, EvalError> {
+        Ok(comparison.test(self.partial_cmp(other)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Ast;
+    use std::collections::HashMap;
+
+    #[test]
+    fn floating_point_rounding() {
+        let ast: Ast<f64> = "0.1 + 0.2".parse().unwrap();
+
+        assert_eq!(ast.eval(&HashMap::new()), Ok(0.30000000000000004));
+    }
+
+    #[test]
+    fn floating_point_division_by_zero() {
+        let ast: Ast<f64> = "1 / 0".parse().unwrap();
+
+        assert_eq!(ast.eval(&HashMap::new()), Ok(f64::INFINITY));
+    }
+
+    #[test]
+    fn unknown_operator() {
+        assert_eq!(
+            2.0.apply_op('%', &1.0),
+            Err(EvalError::UnknownOperator('%'))
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::ast::{Comparison, EvalError, Func};
+use crate::number::Number;
+use crate::unit::{Dimension, Unit};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+// the value is always kept in SI base units, `unit` only remembers how the
+// quantity was written or asked to be converted for display
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    value: f64,
+    dimension: Dimension,
+    unit: Option<Unit>,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: &Unit) -> Self {
+        Quantity {
+            value: value * unit.scale(),
+            dimension: unit.dimension(),
+            unit: Some(unit.clone()),
+        }
+    }
+
+    pub fn dimensionless(value: f64) -> Self {
+        Quantity {
+            value,
+            dimension: Dimension::NONE,
+            unit: None,
+        }
+    }
+
+    pub fn si_value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    pub fn value_in(&self, unit: &Unit) -> Result<f64, EvalError> {
+        self.expect_dimension(unit.dimension())?;
+        Ok(self.value / unit.scale())
+    }
+
+    fn derived(value: f64, dimension: Dimension) -> Self {
+        Quantity {
+            value,
+            dimension,
+            unit: None,
+        }
+    }
+
+    fn expect_dimension(&self, expected: Dimension) -> Result<(), EvalError> {
+        if self.dimension == expected {
+            Ok(())
+        } else {
+            Err(EvalError::IncompatibleUnits(
+                self.dimension.to_string(),
+                expected.to_string(),
+            ))
+        }
+    }
+
+    fn pow(&self, exponent: &Self) -> Result<Self, EvalError> {
+        exponent.expect_dimension(Dimension::NONE)?;
+        let dimension = self.dimension.powf(exponent.value).ok_or_else(|| {
+            EvalError::NotRepresentable(format!("({}) ^ {}", self.dimension, exponent))
+        })?;
+        Ok(Quantity::derived(
+            self.value.powf(exponent.value),
+            dimension,
+        ))
+    }
+}
+
+impl Number for Quantity {
+    fn negate(&self) -> Result<Self, EvalError> {
+        Ok(Quantity {
+            value: -self.value,
+            ..self.clone()
+        })
+    }
+
+    fn is_negative(&self) -> bool {
+        self.value.is_sign_negative()
+    }
+
+    fn apply_op(&self, op: char, other: &Self) -> Result<Self, EvalError> {
+        match op {
+            '+' | '-' => {
+                other.expect_dimension(self.dimension)?;
+                let value = if op == '+' {
+                    self.value + other.value
+                } else {
+                    self.value - other.value
+                };
+                Ok(Quantity::derived(value, self.dimension))
+            }
+            '*' => self
+                .dimension
+                .mul(&other.dimension)
+                .map(|dimension| Quantity::derived(self.value * other.value, dimension))
+                .ok_or(EvalError::Overflow),
+            '/' => self
+                .dimension
+                .div(&other.dimension)
+                .map(|dimension| Quantity::derived(self.value / other.value, dimension))
+                .ok_or(EvalError::Overflow),
+            '^' => self.pow(other),
+            _ => Err(EvalError::UnknownOperator(op)),
+        }
+    }
+
+    fn apply_func(&self, func: Func) -> Result<Self, EvalError> {
+        if func == Func::Sqrt {
+            return self.pow(&Quantity::dimensionless(0.5));
+        }
+        self.expect_dimension(Dimension::NONE)?;
+        Ok(Quantity::dimensionless(func.apply(self.value)))
+    }
+
+    fn compare(&self, comparison: Comparison, other: &Self) -> Result<bool, EvalError> {
+        other.expect_dimension(self.dimension)?;
+        Ok(comparison.test(self.value.partial_cmp(&other.value)))
+    }
+
+    fn with_unit(&self, unit: &Unit) -> Option<Self> {
+        if self.dimension.is_none() && self.unit.is_none() {
+            Some(Quantity::new(self.value, unit))
+        } else {
+            None
+        }
+    }
+
+    fn convert(&self, unit: &Unit) -> Result<Self, EvalError> {
+        self.expect_dimension(unit.dimension())?;
+        Ok(Quantity {
+            unit: Some(unit.clone()),
+            ..self.clone()
+        })
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = ();
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let source = source.trim();
+        let (value, unit) = match source.find(char::is_whitespace) {
+            Some(space) => (&source[..space], Some(source[space..].trim())),
+            None => (source, None),
+        };
+        let value = value.parse().map_err(|_| ())?;
+        match unit {
+            Some(unit) => Ok(Quantity::new(value, &unit.parse()?)),
+            None => Ok(Quantity::dimensionless(value)),
+        }
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.unit {
+            Some(unit) => write!(f, "{} {}", self.value / unit.scale(), unit),
+            None if self.dimension.is_none() => write!(f, "{}", self.value),
+            None => write!(f, "{} {}", self.value, self.dimension),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Ast;
+    use std::collections::HashMap;
+
+    fn evaluated(source: &str) -> Result<Quantity, EvalError> {
+        source
+            .parse::<Ast<Quantity>>()
+            .unwrap()
+            .eval(&HashMap::new())
+    }
+
+    fn displayed(source: &str) -> String {
+        evaluated(source).unwrap().to_string()
+    }
+
+    fn unit(name: &str) -> Unit {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn literals_keep_their_unit() {
+        assert_eq!(displayed("5 km"), "5 km");
+        assert_eq!(evaluated("5 km").unwrap().si_value(), 5000.0);
+        assert_eq!(displayed("9.81 m/s^2"), "9.81 m/s^2");
+    }
+
+    #[test]
+    fn arithmetic_results_are_in_si_units() {
+        assert_eq!(displayed("2 km + 300 m"), "2300 m");
+        assert_eq!(displayed("9.81 m/s^2 * 2 s"), "19.62 m/s");
+        assert_eq!(displayed("3 N * 2 m"), "6 kg*m^2/s^2");
+        assert_eq!(displayed("10 Hz * 2 s"), "20");
+    }
+
+    #[test]
+    fn conversion() {
+        let speed = evaluated("5 km / 2 h to mph").unwrap();
+
+        assert!((speed.value_in(&unit("mph")).unwrap() - 1.553_427_980_593_335).abs() < 1e-12);
+        assert!(speed.to_string().ends_with(" mph"));
+        assert_eq!(displayed("2 h + 30 min to min"), "150 min");
+        assert_eq!(displayed("1 kPa * 2 m^2 to N"), "2000 N");
+    }
+
+    #[test]
+    fn adding_different_dimensions() {
+        assert_eq!(
+            evaluated("3 m + 2 s"),
+            Err(EvalError::IncompatibleUnits("s".to_owned(), "m".to_owned()))
+        );
+    }
+
+    #[test]
+    fn converting_to_a_different_dimension() {
+        assert_eq!(
+            evaluated("2 mph to kg"),
+            Err(EvalError::IncompatibleUnits(
+                "m/s".to_owned(),
+                "kg".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn comparing_different_dimensions() {
+        assert_eq!(displayed("5 km > 300 m ? 1 : 0"), "1");
+        assert_eq!(
+            evaluated("5 km > 300 s ? 1 : 0"),
+            Err(EvalError::IncompatibleUnits("s".to_owned(), "m".to_owned()))
+        );
+    }
+
+    #[test]
+    fn powers_and_roots_scale_dimensions() {
+        assert_eq!(displayed("(3 m) ^ 2"), "9 m^2");
+        assert_eq!(displayed("sqrt(16 m^2)"), "4 m");
+        assert_eq!(
+            evaluated("sqrt(2 m)"),
+            Err(EvalError::NotRepresentable("(m) ^ 0.5".to_owned()))
+        );
+        assert_eq!(
+            evaluated("2 ^ (1 s)"),
+            Err(EvalError::IncompatibleUnits("s".to_owned(), "1".to_owned()))
+        );
+    }
+
+    #[test]
+    fn dimension_exponents_overflow() {
+        assert_eq!(evaluated("(2 m)^100 * (2 m)^100"), Err(EvalError::Overflow));
+        assert_eq!(
+            evaluated("1 / (2 m)^100 / (2 m)^100"),
+            Err(EvalError::Overflow)
+        );
+        assert_eq!(displayed("(1 m)^100 / (1 m)^100"), "1");
+    }
+
+    #[test]
+    fn functions_need_dimensionless_arguments() {
+        assert_eq!(displayed("sin(0 rad)"), "0");
+        assert_eq!(
+            evaluated("exp(1 m)"),
+            Err(EvalError::IncompatibleUnits("m".to_owned(), "1".to_owned()))
+        );
+    }
+
+    #[test]
+    fn variables_carry_units() {
+        let ast: Ast<Quantity> = "distance / time to km/h".parse().unwrap();
+        let vars = vec![
+            ("distance".to_owned(), "42.195 km".parse().unwrap()),
+            ("time".to_owned(), "2 h".parse().unwrap()),
+        ]
+        .into_iter()
+        .collect();
+
+        let speed = ast.eval(&vars).unwrap().value_in(&unit("km/h")).unwrap();
+
+        assert!((speed - 21.0975).abs() < 1e-12);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for source in &["5 km / 2 h to mph", "9.81 m/s^2 * t + 1 m/s", "(-2 m) ^ 2"] {
+            let ast: Ast<Quantity> = source.parse().unwrap();
+
+            assert_eq!(ast.to_string(), *source);
+        }
+    }
+}
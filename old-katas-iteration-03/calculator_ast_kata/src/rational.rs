@@ -0,0 +1,247 @@
+use crate::ast::{Comparison, EvalError, Func};
+use crate::big_int::BigInt;
+use crate::number::Number;
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Rational {
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Result<Self, EvalError> {
+        if denominator.is_zero() {
+            return Err(EvalError::DivisionByZero);
+        }
+        let gcd = numerator.gcd(&denominator);
+        let (numerator, denominator) = (&numerator / &gcd, &denominator / &gcd);
+        if denominator.is_negative() {
+            Ok(Rational {
+                numerator: -&numerator,
+                denominator: -&denominator,
+            })
+        } else {
+            Ok(Rational {
+                numerator,
+                denominator,
+            })
+        }
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == BigInt::one()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator.to_f64() / self.denominator.to_f64()
+    }
+
+    fn pow(&self, exponent: &Rational) -> Result<Self, EvalError> {
+        if !exponent.is_integer() {
+            return Err(EvalError::NotRepresentable(format!(
+                "{} ^ {}",
+                self, exponent
+            )));
+        }
+        let power = exponent
+            .numerator
+            .abs()
+            .to_u32()
+            .ok_or(EvalError::Overflow)?;
+        let (numerator, denominator) = (self.numerator.pow(power), self.denominator.pow(power));
+        if exponent.numerator.is_negative() {
+            Rational::new(denominator, numerator)
+        } else {
+            Rational::new(numerator, denominator)
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational {
+            numerator: BigInt::from(value),
+            denominator: BigInt::one(),
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Number for Rational {
+    fn negate(&self) -> Result<Self, EvalError> {
+        Ok(Rational {
+            numerator: -&self.numerator,
+            denominator: self.denominator.clone(),
+        })
+    }
+
+    fn is_negative(&self) -> bool {
+        self.numerator.is_negative()
+    }
+
+    fn apply_op(&self, op: char, other: &Self) -> Result<Self, EvalError> {
+        let (a, b, c, d) = (
+            &self.numerator,
+            &self.denominator,
+            &other.numerator,
+            &other.denominator,
+        );
+        match op {
+            '+' => Rational::new(&(a * d) + &(c * b), b * d),
+            '-' => Rational::new(&(a * d) - &(c * b), b * d),
+            '*' => Rational::new(a * c, b * d),
+            '/' => Rational::new(a * d, b * c),
+            '^' => self.pow(other),
+            _ => Err(EvalError::UnknownOperator(op)),
+        }
+    }
+
+    fn apply_func(&self, func: Func) -> Result<Self, EvalError> {
+        Err(EvalError::NotRepresentable(format!("{}({})", func, self)))
+    }
+    fn compare(&self, comparison: Comparison, other: &Self) -> Result<bool, EvalError> {
+        Ok(comparison.test(Some(self.cmp(other))))
+    }
+}
+
+impl FromStr for Rational {
+    type Err = ();
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let (integer, fraction) = match source.find('.') {
+            Some(dot) => (&source[..dot], &source[dot + 1..]),
+            None => (source, ""),
+        };
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(());
+        }
+        let numerator = format!("{}{}", integer, fraction).parse()?;
+        let denominator = BigInt::from(10).pow(fraction.len() as u32);
+        Rational::new(numerator, denominator).map_err(|_| ())
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Ast;
+    use std::collections::HashMap;
+
+    fn exact(source: &str) -> Result<Rational, EvalError> {
+        source
+            .parse::<Ast<Rational>>()
+            .unwrap()
+            .eval(&HashMap::new())
+    }
+
+    fn rational(source: &str) -> Rational {
+        source.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_decimal_literal() {
+        assert_eq!(rational("0.1").to_string(), "1/10");
+        assert_eq!(rational("1.25").to_string(), "5/4");
+        assert_eq!(rational("40.0").to_string(), "40");
+        assert_eq!(rational(".5").to_string(), "1/2");
+    }
+
+    #[test]
+    fn exact_addition() {
+        assert_eq!(exact("0.1 + 0.2"), Ok(rational("0.3")));
+    }
+
+    #[test]
+    fn exact_division() {
+        assert_eq!(
+            exact("1/3 + 1/6").map(|r| r.to_string()),
+            Ok("1/2".to_owned())
+        );
+        assert_eq!(exact("-2/4").map(|r| r.to_string()), Ok("-1/2".to_owned()));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(exact("1/(0.5-0.5)"), Err(EvalError::DivisionByZero));
+    }
+
+    #[test]
+    fn integer_power() {
+        assert_eq!(
+            exact("2^100").map(|r| r.to_string()),
+            Ok("1267650600228229401496703205376".to_owned())
+        );
+        assert_eq!(
+            exact("(2/3)^-2").map(|r| r.to_string()),
+            Ok("9/4".to_owned())
+        );
+    }
+
+    #[test]
+    fn fractional_power_is_not_representable() {
+        assert_eq!(
+            exact("2^0.5"),
+            Err(EvalError::NotRepresentable("2 ^ 1/2".to_owned()))
+        );
+    }
+
+    #[test]
+    fn functions_are_not_representable() {
+        assert_eq!(
+            exact("sin(1)"),
+            Err(EvalError::NotRepresentable("sin(1)".to_owned()))
+        );
+    }
+
+    #[test]
+    fn billing_formula() {
+        let ast: Ast<Rational> = "price * qty * (1 - discount)".parse().unwrap();
+        let vars = vec![
+            ("price".to_owned(), rational("19.99")),
+            ("qty".to_owned(), rational("3")),
+            ("discount".to_owned(), rational("0.15")),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(ast.eval(&vars), Ok(rational("50.9745")));
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(rational("0.3") > rational("0.25"));
+        assert!(rational("0.3").negate().unwrap() < rational("0.25"));
+    }
+}
@@ -0,0 +1,366 @@
+use crate::ast::Ast;
+use std::cmp::Ordering;
+
+impl Ast {
+    pub fn simplify(&self) -> Ast {
+        match self {
+            Ast::Num(_) | Ast::Var(_) => self.clone(),
+            Ast::Neg(_) | Ast::Op('+', _, _) | Ast::Op('-', _, _) => simplify_sum(self),
+            Ast::Op('*', _, _) => simplify_product(self),
+            Ast::Op('/', left, right) => simplify_division(left.simplify(), right.simplify()),
+            Ast::Op('^', base, exponent) => simplify_power(base.simplify(), exponent.simplify()),
+            Ast::Op(op, left, right) => Ast::op(*op, left.simplify(), right.simplify()),
+            Ast::Call(func, arg) => match arg.simplify() {
+                Ast::Num(num) => folded(func.apply(num), || Ast::call(*func, Ast::Num(num))),
+                arg => Ast::call(*func, arg),
+            },
+            Ast::Bool(_) => self.clone(),
+            Ast::Not(operand) => match operand.simplify() {
+                Ast::Bool(value) => Ast::Bool(!value),
+                operand => Ast::logical_not(operand),
+            },
+            Ast::Cmp(comparison, left, right) => match (left.simplify(), right.simplify()) {
+                (Ast::Num(left), Ast::Num(right)) => {
+                    Ast::Bool(comparison.test(left.partial_cmp(&right)))
+                }
+                (left, right) => Ast::compare(*comparison, left, right),
+            },
+            Ast::And(left, right) => match left.simplify() {
+                Ast::Bool(true) => right.simplify(),
+                Ast::Bool(false) => Ast::Bool(false),
+                left => Ast::and(left, right.simplify()),
+            },
+            Ast::Or(left, right) => match left.simplify() {
+                Ast::Bool(true) => Ast::Bool(true),
+                Ast::Bool(false) => right.simplify(),
+                left => Ast::or(left, right.simplify()),
+            },
+            Ast::If(condition, then, otherwise) => match condition.simplify() {
+                Ast::Bool(true) => then.simplify(),
+                Ast::Bool(false) => otherwise.simplify(),
+                condition => Ast::conditional(condition, then.simplify(), otherwise.simplify()),
+            },
+            Ast::Convert(operand, unit) => Ast::convert(operand.simplify(), unit.clone()),
+        }
+    }
+}
+
+fn simplify_sum(ast: &Ast) -> Ast {
+    let mut terms = vec![];
+    let mut constant = 0.0;
+    collect_terms(ast, 1.0, &mut terms, &mut constant);
+
+    let mut combined: Vec<(f64, Ast)> = vec![];
+    for (coefficient, term) in terms {
+        match combined.iter_mut().find(|(_, existing)| *existing == term) {
+            Some((existing, _)) => *existing += coefficient,
+            None => combined.push((coefficient, term)),
+        }
+    }
+    combined.retain(|&(coefficient, _)| coefficient != 0.0);
+    combined.sort_by(|(_, left), (_, right)| canonical_cmp(left, right));
+
+    let mut root: Option<Ast> = None;
+    for (coefficient, term) in combined {
+        root = Some(match root {
+            None => scale(coefficient, term),
+            Some(left) if coefficient < 0.0 => Ast::op('-', left, scale(-coefficient, term)),
+            Some(left) => Ast::op('+', left, scale(coefficient, term)),
+        });
+    }
+    match root {
+        None => Ast::Num(constant),
+        Some(root) if constant == 0.0 => root,
+        Some(root) if constant < 0.0 => Ast::op('-', root, Ast::Num(-constant)),
+        Some(root) => Ast::op('+', root, Ast::Num(constant)),
+    }
+}
+
+fn collect_terms(ast: &Ast, sign: f64, terms: &mut Vec<(f64, Ast)>, constant: &mut f64) {
+    match ast {
+        Ast::Op('+', left, right) => {
+            collect_terms(left, sign, terms, constant);
+            collect_terms(right, sign, terms, constant);
+        }
+        Ast::Op('-', left, right) => {
+            collect_terms(left, sign, terms, constant);
+            collect_terms(right, -sign, terms, constant);
+        }
+        Ast::Neg(operand) => collect_terms(operand, -sign, terms, constant),
+        _ => match ast.simplify() {
+            Ast::Num(num) => *constant += sign * num,
+            sum @ Ast::Op('+', _, _) | sum @ Ast::Op('-', _, _) | sum @ Ast::Neg(_) => {
+                collect_terms(&sum, sign, terms, constant)
+            }
+            Ast::Op('*', coefficient, term) => match *coefficient {
+                Ast::Num(coefficient) => terms.push((sign * coefficient, *term)),
+                coefficient => terms.push((sign, Ast::Op('*', Box::new(coefficient), term))),
+            },
+            term => terms.push((sign, term)),
+        },
+    }
+}
+
+fn scale(coefficient: f64, term: Ast) -> Ast {
+    if coefficient == 1.0 {
+        term
+    } else if coefficient == -1.0 {
+        Ast::negate(term)
+    } else {
+        Ast::op('*', Ast::Num(coefficient), term)
+    }
+}
+
+fn simplify_product(ast: &Ast) -> Ast {
+    let mut factors = vec![];
+    let mut coefficient = 1.0;
+    collect_factors(ast, &mut factors, &mut coefficient);
+    if coefficient == 0.0 {
+        return Ast::Num(0.0);
+    }
+
+    // `x^0.5 * x^0.5` is not `x` for a negative `x`, only whole powers merge
+    let mut combined: Vec<(Ast, f64)> = vec![];
+    for (base, exponent) in factors {
+        let like = combined.iter_mut().find(|(existing, existing_exponent)| {
+            *existing == base && existing_exponent.fract() == 0.0 && exponent.fract() == 0.0
+        });
+        match like {
+            Some((_, existing)) => *existing += exponent,
+            None => combined.push((base, exponent)),
+        }
+    }
+    combined.retain(|&(_, exponent)| exponent != 0.0);
+    combined.sort_by(|(left, _), (right, _)| canonical_cmp(left, right));
+
+    let product = combined
+        .into_iter()
+        .map(|(base, exponent)| simplify_power(base, Ast::Num(exponent)))
+        .fold(None, |root, factor| match root {
+            None => Some(factor),
+            Some(left) => Some(Ast::op('*', left, factor)),
+        });
+    match product {
+        None => Ast::Num(coefficient),
+        Some(product) => scale(coefficient, product),
+    }
+}
+
+fn collect_factors(ast: &Ast, factors: &mut Vec<(Ast, f64)>, coefficient: &mut f64) {
+    match ast {
+        Ast::Op('*', left, right) => {
+            collect_factors(left, factors, coefficient);
+            collect_factors(right, factors, coefficient);
+        }
+        _ => match ast.simplify() {
+            Ast::Num(num) => *coefficient *= num,
+            product @ Ast::Op('*', _, _) => collect_factors(&product, factors, coefficient),
+            Ast::Neg(operand) => {
+                *coefficient = -*coefficient;
+                collect_factors(&operand, factors, coefficient);
+            }
+            Ast::Op('^', base, exponent) => match *exponent {
+                Ast::Num(exponent) => factors.push((*base, exponent)),
+                exponent => factors.push((Ast::Op('^', base, Box::new(exponent)), 1.0)),
+            },
+            factor => factors.push((factor, 1.0)),
+        },
+    }
+}
+
+fn simplify_division(dividend: Ast, divisor: Ast) -> Ast {
+    match (dividend, divisor) {
+        (Ast::Num(dividend), Ast::Num(divisor)) => folded(dividend / divisor, || {
+            Ast::op('/', Ast::Num(dividend), Ast::Num(divisor))
+        }),
+        (dividend, Ast::Num(1.0)) => dividend,
+        (Ast::Op('*', coefficient, term), Ast::Num(divisor)) => match *coefficient {
+            Ast::Num(coefficient) => scale(coefficient / divisor, *term),
+            coefficient => Ast::op(
+                '/',
+                Ast::Op('*', Box::new(coefficient), term),
+                Ast::Num(divisor),
+            ),
+        },
+        (dividend, divisor) => Ast::op('/', dividend, divisor),
+    }
+}
+
+fn simplify_power(base: Ast, exponent: Ast) -> Ast {
+    match (base, exponent) {
+        (Ast::Num(base), Ast::Num(exponent)) => folded(base.powf(exponent), || {
+            Ast::op('^', Ast::Num(base), Ast::Num(exponent))
+        }),
+        (_, Ast::Num(0.0)) => Ast::Num(1.0),
+        (base, Ast::Num(1.0)) => base,
+        (Ast::Num(1.0), _) => Ast::Num(1.0),
+        (base, exponent) => Ast::op('^', base, exponent),
+    }
+}
+
+// NaN and infinities have no literal to print back, such a constant stays unfolded
+fn folded(value: f64, unfolded: impl FnOnce() -> Ast) -> Ast {
+    if value.is_finite() {
+        Ast::Num(value)
+    } else {
+        unfolded()
+    }
+}
+
+fn rank(ast: &Ast) -> u8 {
+    match ast {
+        Ast::Num(_) => 0,
+        Ast::Bool(_) => 1,
+        Ast::Var(_) => 2,
+        Ast::Call(_, _) => 3,
+        Ast::Neg(_) => 4,
+        Ast::Not(_) => 5,
+        Ast::Op(_, _, _) => 6,
+        Ast::Cmp(_, _, _) => 7,
+        Ast::And(_, _) => 8,
+        Ast::Or(_, _) => 9,
+        Ast::If(_, _, _) => 10,
+        Ast::Convert(_, _) => 11,
+    }
+}
+
+fn canonical_cmp(left: &Ast, right: &Ast) -> Ordering {
+    match (left, right) {
+        (Ast::Num(left), Ast::Num(right)) => left.total_cmp(right),
+        (Ast::Bool(left), Ast::Bool(right)) => left.cmp(right),
+        (Ast::Var(left), Ast::Var(right)) => left.cmp(right),
+        (Ast::Call(left_func, left_arg), Ast::Call(right_func, right_arg)) => left_func
+            .cmp(right_func)
+            .then_with(|| canonical_cmp(left_arg, right_arg)),
+        (Ast::Neg(left), Ast::Neg(right)) => canonical_cmp(left, right),
+        (Ast::Op(left_op, left_left, left_right), Ast::Op(right_op, right_left, right_right)) => {
+            left_op
+                .cmp(right_op)
+                .then_with(|| canonical_cmp(left_left, right_left))
+                .then_with(|| canonical_cmp(left_right, right_right))
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn simplified(source: &str) -> String {
+        source.parse::<Ast>().unwrap().simplify().to_string()
+    }
+
+    #[test]
+    fn fold_constant_conditions() {
+        assert_eq!(simplified("1 < 2 ? x : y"), "x");
+        assert_eq!(simplified("!(2 > 3) && x > 0"), "x > 0");
+        assert_eq!(simplified("x > 0 || 1 == 1"), "x > 0 || true");
+        assert_eq!(simplified("x + x > 0 ? 0 * y : y"), "2 * x > 0 ? 0 : y");
+    }
+
+    #[test]
+    fn fold_constants() {
+        assert_eq!(simplified("2*3+4/2-1"), "7");
+    }
+
+    #[test]
+    fn fold_functions_of_constants() {
+        assert_eq!(simplified("sqrt(16)+ln(1)"), "4");
+    }
+
+    #[test]
+    fn addition_of_zero() {
+        assert_eq!(simplified("x+0"), "x");
+        assert_eq!(simplified("0+x"), "x");
+    }
+
+    #[test]
+    fn multiplication_by_one() {
+        assert_eq!(simplified("x*1"), "x");
+        assert_eq!(simplified("1*x"), "x");
+    }
+
+    #[test]
+    fn multiplication_by_zero() {
+        assert_eq!(simplified("x*0"), "0");
+        assert_eq!(simplified("0*(x+y)"), "0");
+    }
+
+    #[test]
+    fn subtraction_of_itself() {
+        assert_eq!(simplified("x-x"), "0");
+        assert_eq!(simplified("sin(x)*y-y*sin(x)"), "0");
+    }
+
+    #[test]
+    fn flatten_associative_chains() {
+        assert_eq!(simplified("1+x+2"), "x + 3");
+        assert_eq!(simplified("2*x*3"), "6 * x");
+        assert_eq!(simplified("(x+1)-(2-y)"), "x + y - 1");
+    }
+
+    #[test]
+    fn combine_like_terms_and_factors() {
+        assert_eq!(simplified("x+2*x-y"), "3 * x - y");
+        assert_eq!(simplified("x*x*y/2"), "x ^ 2 * y / 2");
+    }
+
+    #[test]
+    fn canonical_operand_order() {
+        assert_eq!(simplified("y+x"), "x + y");
+        assert_eq!(simplified("y*x"), simplified("x*y"));
+        assert_eq!(simplified("-x+y"), "-x + y");
+    }
+
+    #[test]
+    fn identities_of_power() {
+        assert_eq!(simplified("x^1"), "x");
+        assert_eq!(simplified("x^0"), "1");
+        assert_eq!(simplified("1^x"), "1");
+        assert_eq!(simplified("2^10*x"), "1024 * x");
+    }
+
+    #[test]
+    fn undefined_constants_stay_unfolded() {
+        assert_eq!(simplified("0/0 + 1"), "0 / 0 + 1");
+        assert_eq!(simplified("1/0"), "1 / 0");
+        assert_eq!(simplified("sqrt(-1)"), "sqrt(-1)");
+        assert_eq!(simplified("(-8)^(1/3)"), "(-8) ^ 0.3333333333333333");
+        assert!(simplified("0/0").parse::<Ast>().is_ok());
+    }
+
+    #[test]
+    fn fractional_powers_do_not_merge() {
+        assert_eq!(simplified("x^0.5 * x^0.5"), "x ^ 0.5 * x ^ 0.5");
+        assert_eq!(simplified("x^2 * x^0.5 * x"), "x ^ 3 * x ^ 0.5");
+        assert_eq!(simplified("x^2 * x^-2"), "1");
+    }
+
+    #[test]
+    fn division_by_one() {
+        assert_eq!(simplified("(x+y)/1"), "x + y");
+        assert_eq!(simplified("6*x/3"), "2 * x");
+    }
+
+    #[test]
+    fn simplified_evaluates_the_same() {
+        let ast: Ast = "(3*x - 2^2*y + x*sqrt(4)) / (1 + 1) - (y - y)"
+            .parse()
+            .unwrap();
+        let vars: HashMap<String, f64> = vec![("x".to_owned(), 3.0), ("y".to_owned(), 0.5)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(ast.simplify().to_string(), "(5 * x - 4 * y) / 2");
+        assert_eq!(ast.simplify().eval(&vars), ast.eval(&vars));
+    }
+
+    #[test]
+    fn simplify_is_idempotent() {
+        let ast = "2*x*(y+1)+3-x*(1+y)".parse::<Ast>().unwrap().simplify();
+
+        assert_eq!(ast.simplify(), ast);
+    }
+}
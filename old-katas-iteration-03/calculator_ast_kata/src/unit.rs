@@ -0,0 +1,336 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+const BASE_SYMBOLS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+// exponents of kilogram, metre, second, ampere, kelvin, mole and candela
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Dimension([i8; 7]);
+
+impl Dimension {
+    pub const NONE: Dimension = Dimension([0; 7]);
+
+    pub fn is_none(&self) -> bool {
+        *self == Dimension::NONE
+    }
+
+    // `None` when an exponent leaves the i8 range
+    pub fn mul(&self, other: &Dimension) -> Option<Dimension> {
+        self.combine(other, i8::checked_add)
+    }
+
+    pub fn div(&self, other: &Dimension) -> Option<Dimension> {
+        self.combine(other, i8::checked_sub)
+    }
+
+    pub fn powi(&self, power: i8) -> Option<Dimension> {
+        self.combine(&Dimension::NONE, |exponent, _| exponent.checked_mul(power))
+    }
+
+    pub fn powf(&self, power: f64) -> Option<Dimension> {
+        let mut exponents = [0; 7];
+        for (result, &exponent) in exponents.iter_mut().zip(self.0.iter()) {
+            let scaled = exponent as f64 * power;
+            if scaled.fract() != 0.0 || scaled.abs() > i8::MAX as f64 {
+                return None;
+            }
+            *result = scaled as i8;
+        }
+        Some(Dimension(exponents))
+    }
+
+    fn combine(&self, other: &Dimension, op: impl Fn(i8, i8) -> Option<i8>) -> Option<Dimension> {
+        let mut exponents = [0; 7];
+        for (index, exponent) in exponents.iter_mut().enumerate() {
+            *exponent = op(self.0[index], other.0[index])?;
+        }
+        Some(Dimension(exponents))
+    }
+}
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let factors = |sign: i8| {
+            BASE_SYMBOLS
+                .iter()
+                .zip(self.0.iter())
+                .filter(move |&(_, &exponent)| exponent.signum() == sign)
+                .map(move |(symbol, &exponent)| match exponent * sign {
+                    1 => symbol.to_string(),
+                    exponent => format!("{}^{}", symbol, exponent),
+                })
+                .collect::<Vec<_>>()
+        };
+        let numerator = factors(1);
+        let denominator = factors(-1);
+        if self.is_none() {
+            f.write_str("1")
+        } else if numerator.is_empty() {
+            // a bare denominator has nothing to divide, so keep the negative powers
+            let inverse = BASE_SYMBOLS
+                .iter()
+                .zip(self.0.iter())
+                .filter(|&(_, &exponent)| exponent < 0)
+                .map(|(symbol, exponent)| format!("{}^{}", symbol, exponent))
+                .collect::<Vec<_>>();
+            f.write_str(&inverse.join("*"))
+        } else if denominator.is_empty() {
+            f.write_str(&numerator.join("*"))
+        } else {
+            write!(f, "{}/{}", numerator.join("*"), denominator.join("/"))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    name: String,
+    scale: f64,
+    dimension: Dimension,
+}
+
+struct Definition {
+    symbol: &'static str,
+    scale: f64,
+    dimension: [i8; 7],
+    prefixable: bool,
+}
+
+const fn si(symbol: &'static str, scale: f64, dimension: [i8; 7]) -> Definition {
+    Definition {
+        symbol,
+        scale,
+        dimension,
+        prefixable: true,
+    }
+}
+
+const fn other(symbol: &'static str, scale: f64, dimension: [i8; 7]) -> Definition {
+    Definition {
+        symbol,
+        scale,
+        dimension,
+        prefixable: false,
+    }
+}
+
+//                kg  m   s   A  K mol cd
+const UNITS: &[Definition] = &[
+    si("g", 1e-3, [1, 0, 0, 0, 0, 0, 0]),
+    si("m", 1.0, [0, 1, 0, 0, 0, 0, 0]),
+    si("s", 1.0, [0, 0, 1, 0, 0, 0, 0]),
+    si("A", 1.0, [0, 0, 0, 1, 0, 0, 0]),
+    si("K", 1.0, [0, 0, 0, 0, 1, 0, 0]),
+    si("mol", 1.0, [0, 0, 0, 0, 0, 1, 0]),
+    si("cd", 1.0, [0, 0, 0, 0, 0, 0, 1]),
+    si("Hz", 1.0, [0, 0, -1, 0, 0, 0, 0]),
+    si("N", 1.0, [1, 1, -2, 0, 0, 0, 0]),
+    si("Pa", 1.0, [1, -1, -2, 0, 0, 0, 0]),
+    si("J", 1.0, [1, 2, -2, 0, 0, 0, 0]),
+    si("W", 1.0, [1, 2, -3, 0, 0, 0, 0]),
+    si("C", 1.0, [0, 0, 1, 1, 0, 0, 0]),
+    si("V", 1.0, [1, 2, -3, -1, 0, 0, 0]),
+    si("ohm", 1.0, [1, 2, -3, -2, 0, 0, 0]),
+    si("Ω", 1.0, [1, 2, -3, -2, 0, 0, 0]),
+    si("S", 1.0, [-1, -2, 3, 2, 0, 0, 0]),
+    si("F", 1.0, [-1, -2, 4, 2, 0, 0, 0]),
+    si("Wb", 1.0, [1, 2, -2, -1, 0, 0, 0]),
+    si("T", 1.0, [1, 0, -2, -1, 0, 0, 0]),
+    si("H", 1.0, [1, 2, -2, -2, 0, 0, 0]),
+    si("L", 1e-3, [0, 3, 0, 0, 0, 0, 0]),
+    si("rad", 1.0, [0, 0, 0, 0, 0, 0, 0]),
+    other("min", 60.0, [0, 0, 1, 0, 0, 0, 0]),
+    other("h", 3600.0, [0, 0, 1, 0, 0, 0, 0]),
+    other("day", 86400.0, [0, 0, 1, 0, 0, 0, 0]),
+    other("in", 0.0254, [0, 1, 0, 0, 0, 0, 0]),
+    other("ft", 0.3048, [0, 1, 0, 0, 0, 0, 0]),
+    other("yd", 0.9144, [0, 1, 0, 0, 0, 0, 0]),
+    other("mi", 1609.344, [0, 1, 0, 0, 0, 0, 0]),
+    other("mph", 0.44704, [0, 1, -1, 0, 0, 0, 0]),
+    other("lb", 0.453_592_37, [1, 0, 0, 0, 0, 0, 0]),
+];
+
+const PREFIXES: &[(&str, f64)] = &[
+    ("Y", 1e24),
+    ("Z", 1e21),
+    ("E", 1e18),
+    ("P", 1e15),
+    ("T", 1e12),
+    ("G", 1e9),
+    ("M", 1e6),
+    ("k", 1e3),
+    ("h", 1e2),
+    ("da", 1e1),
+    ("d", 1e-1),
+    ("c", 1e-2),
+    ("m", 1e-3),
+    ("µ", 1e-6),
+    ("u", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+    ("f", 1e-15),
+    ("a", 1e-18),
+    ("z", 1e-21),
+    ("y", 1e-24),
+];
+
+impl Unit {
+    pub fn lookup(name: &str) -> Option<Unit> {
+        let unit = |definition: &Definition, prefix: f64| Unit {
+            name: name.to_owned(),
+            scale: prefix * definition.scale,
+            dimension: Dimension(definition.dimension),
+        };
+        if let Some(definition) = UNITS.iter().find(|unit| unit.symbol == name) {
+            return Some(unit(definition, 1.0));
+        }
+        PREFIXES.iter().find_map(|&(prefix, factor)| {
+            let symbol = name.strip_prefix(prefix)?;
+            UNITS
+                .iter()
+                .find(|unit| unit.prefixable && unit.symbol == symbol)
+                .map(|definition| unit(definition, factor))
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    pub fn mul(&self, other: &Unit) -> Option<Unit> {
+        Some(Unit {
+            name: format!("{}*{}", self.name, other.name),
+            scale: self.scale * other.scale,
+            dimension: self.dimension.mul(&other.dimension)?,
+        })
+    }
+
+    pub fn div(&self, other: &Unit) -> Option<Unit> {
+        Some(Unit {
+            name: format!("{}/{}", self.name, other.name),
+            scale: self.scale / other.scale,
+            dimension: self.dimension.div(&other.dimension)?,
+        })
+    }
+
+    pub fn powi(&self, power: i8) -> Option<Unit> {
+        Some(Unit {
+            name: format!("{}^{}", self.name, power),
+            scale: self.scale.powi(power as i32),
+            dimension: self.dimension.powi(power)?,
+        })
+    }
+}
+
+impl FromStr for Unit {
+    type Err = ();
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let factor = |factor: &str| match factor.find('^') {
+            Some(caret) => {
+                let power = factor[caret + 1..].parse().map_err(|_| ())?;
+                Unit::lookup(&factor[..caret])
+                    .and_then(|unit| unit.powi(power))
+                    .ok_or(())
+            }
+            None => Unit::lookup(factor).ok_or(()),
+        };
+        let mut unit: Option<Unit> = None;
+        let mut start = 0;
+        let mut op = '*';
+        for (index, char) in source.char_indices().chain(Some((source.len(), '*'))) {
+            if char == '*' || char == '/' {
+                let next = factor(source[start..index].trim())?;
+                unit = Some(match (unit, op) {
+                    (None, _) => next,
+                    (Some(unit), '*') => unit.mul(&next).ok_or(())?,
+                    (Some(unit), _) => unit.div(&next).ok_or(())?,
+                });
+                start = index + char.len_utf8();
+                op = char;
+            }
+        }
+        unit.ok_or(())
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(name: &str) -> Unit {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn base_units() {
+        assert_eq!(unit("m").dimension().to_string(), "m");
+        assert_eq!(unit("kg").dimension().to_string(), "kg");
+        assert_eq!(unit("kg").scale(), 1.0);
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(unit("km").scale(), 1e3);
+        assert_eq!(unit("µs").scale(), 1e-6);
+        assert_eq!(unit("mmol").dimension(), unit("mol").dimension());
+        assert_eq!(unit("dam").scale(), 10.0);
+    }
+
+    #[test]
+    fn exact_symbols_win_over_prefixes() {
+        assert_eq!(unit("min").scale(), 60.0);
+        assert_eq!(unit("mm").scale(), 1e-3);
+        assert_eq!(unit("h").scale(), 3600.0);
+        assert_eq!(unit("Pa").dimension().to_string(), "kg/m/s^2");
+        assert_eq!(unit("ft").scale(), 0.3048);
+    }
+
+    #[test]
+    fn only_si_units_take_prefixes() {
+        assert_eq!(Unit::lookup("kmi"), None);
+        assert_eq!(Unit::lookup("kh"), None);
+        assert_eq!(Unit::lookup("xyz"), None);
+    }
+
+    #[test]
+    fn compound_units() {
+        assert_eq!(unit("kg*m/s^2").dimension(), unit("N").dimension());
+        assert_eq!(unit("km/h").scale(), 1000.0 / 3600.0);
+        assert_eq!(unit("km/h").to_string(), "km/h");
+        assert_eq!(unit("W/m^2").dimension().to_string(), "kg/s^3");
+        assert_eq!("m/".parse::<Unit>(), Err(()));
+    }
+
+    #[test]
+    fn exponents_out_of_range() {
+        let m100 = unit("m^100").dimension();
+
+        assert_eq!(m100.mul(&m100), None);
+        assert_eq!(m100.powi(-1).and_then(|inverse| inverse.div(&m100)), None);
+        assert_eq!(m100.powi(2), None);
+        assert_eq!("m^100*m^100".parse::<Unit>(), Err(()));
+        assert_eq!("m^-100/m^100".parse::<Unit>(), Err(()));
+    }
+
+    #[test]
+    fn derived_dimensions() {
+        assert_eq!(unit("V").dimension().to_string(), "kg*m^2/s^3/A");
+        assert_eq!(unit("Hz").dimension().to_string(), "s^-1");
+        assert_eq!(unit("rad").dimension().to_string(), "1");
+    }
+}
@@ -0,0 +1,158 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(input: &str) -> Output {
+    let mut calc = Command::new(env!("CARGO_BIN_EXE_calc"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("calc binary is built for integration tests");
+    calc.stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    calc.wait_with_output().unwrap()
+}
+
+fn stdout(input: &str) -> String {
+    String::from_utf8(run(input).stdout).unwrap()
+}
+
+fn stderr(input: &str) -> String {
+    String::from_utf8(run(input).stderr).unwrap()
+}
+
+#[test]
+fn evaluate_expression() {
+    assert_eq!(stdout("1 + 2 * 3\n"), "_1 = 7\n");
+}
+
+#[test]
+fn empty_lines_are_ignored() {
+    assert_eq!(stdout("\n  \n2^3\n"), "_1 = 8\n");
+}
+
+#[test]
+fn variables_persist_across_lines() {
+    assert_eq!(
+        stdout("price = 2.5\nqty = 4\nprice * qty\n"),
+        "price = 2.5\nqty = 4\n_1 = 10\n"
+    );
+}
+
+#[test]
+fn previous_results_are_numbered() {
+    assert_eq!(
+        stdout("2 + 2\n_1 * 10\n_2 - _1\n"),
+        "_1 = 4\n_2 = 40\n_3 = 36\n"
+    );
+}
+
+#[test]
+fn dump_ast() {
+    assert_eq!(
+        stdout(":ast 1 + x * 2\n"),
+        "Op +\n  Num 1\n  Op *\n    Var x\n    Num 2\n"
+    );
+}
+
+#[test]
+fn dump_bytecode() {
+    assert_eq!(
+        stdout(":bytecode -sin(x)\n"),
+        "0000 load 0 (x)\n0001 call sin\n0002 neg\n"
+    );
+}
+
+#[test]
+fn list_variables() {
+    assert_eq!(
+        stdout("b = 2\na = 1\na + b\n:vars\n"),
+        "b = 2\na = 1\n_1 = 3\n_1 = 3\na = 1\nb = 2\n"
+    );
+}
+
+#[test]
+fn help() {
+    assert!(stdout(":help\n").contains(":bytecode <expr>"));
+}
+
+#[test]
+fn quit_stops_reading() {
+    assert_eq!(stdout("1\n:quit\n2\n"), "_1 = 1\n");
+}
+
+#[test]
+fn syntax_error_points_at_column() {
+    assert_eq!(
+        stderr("1 + * 2\n"),
+        "error: unexpected `*` at column 5\n  1 + * 2\n      ^\n"
+    );
+}
+
+#[test]
+fn unclosed_parenthesis_points_at_opening() {
+    assert_eq!(
+        stderr("2 * (1 + x\n"),
+        "error: unclosed parenthesis at column 5\n  2 * (1 + x\n      ^\n"
+    );
+}
+
+#[test]
+fn unknown_variable() {
+    let output = run("x + 1\n2\n");
+
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: unknown variable `x`\n"
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "_1 = 2\n");
+}
+
+#[test]
+fn unknown_command() {
+    assert_eq!(
+        stderr(":foo\n"),
+        "error: unknown command `:foo`, try :help\n"
+    );
+}
+
+#[test]
+fn conditional_pricing_rule() {
+    assert_eq!(
+        stdout("price = 10\nqty = 150\nqty > 100 ? price * 0.9 : price\nqty > 200\n"),
+        "price = 10\nqty = 150\n_1 = 9\nfalse\n"
+    );
+}
+
+#[test]
+fn type_mismatch() {
+    assert_eq!(
+        stderr("1 + (2 < 3)\n"),
+        "error: expected number, found boolean\n"
+    );
+}
+
+#[test]
+fn reserved_words_cannot_be_assigned() {
+    assert_eq!(
+        stderr("true = 1\nto = 1\n"),
+        "error: `true` is a reserved word\nerror: `to` is a reserved word\n"
+    );
+}
+
+#[test]
+fn previous_results_cannot_be_assigned() {
+    let output = run("2\n_1 = 5\n_1\n");
+
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: `_1` is a previous result and can't be assigned\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "_1 = 2\n_2 = 2\n"
+    );
+}
@@ -0,0 +1,327 @@
+use std::iter::FromIterator;
+
+// items on even levels are not greater than anything below them,
+// items on odd levels are not less than anything below them
+pub struct MinMaxHeap<T> {
+    items: Vec<T>,
+}
+
+fn is_min_level(index: usize) -> bool {
+    (index + 1).leading_zeros() % 2 == 1usize.leading_zeros() % 2
+}
+
+fn parent(index: usize) -> usize {
+    (index - 1) / 2
+}
+
+impl<T: Ord> MinMaxHeap<T> {
+    pub fn new() -> Self {
+        Self { items: vec![] }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn peek_min(&self) -> Option<&T> {
+        self.items.first()
+    }
+
+    pub fn peek_max(&self) -> Option<&T> {
+        self.max_index().map(|index| &self.items[index])
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+        self.bubble_up(self.items.len() - 1);
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.remove(0)
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.max_index().and_then(|index| self.remove(index))
+    }
+
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(item) = self.pop_min() {
+            sorted.push(item);
+        }
+        sorted
+    }
+
+    fn max_index(&self) -> Option<usize> {
+        match self.items.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ if self.items[1] >= self.items[2] => Some(1),
+            _ => Some(2),
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.items.len() {
+            return None;
+        }
+        let item = self.items.swap_remove(index);
+        if index < self.items.len() {
+            self.trickle_down(index);
+        }
+        Some(item)
+    }
+
+    fn bubble_up(&mut self, index: usize) {
+        if index == 0 {
+            return;
+        }
+        let parent = parent(index);
+        let min_level = is_min_level(index);
+        if self.in_order(index, parent, !min_level) {
+            self.items.swap(index, parent);
+            self.bubble_up_to_grandparents(parent, !min_level);
+        } else {
+            self.bubble_up_to_grandparents(index, min_level);
+        }
+    }
+
+    // `min_level` flips the comparison, so one walk serves both kinds of levels
+    fn bubble_up_to_grandparents(&mut self, mut index: usize, min_level: bool) {
+        while index > 2 {
+            let grandparent = parent(parent(index));
+            if !self.in_order(index, grandparent, min_level) {
+                break;
+            }
+            self.items.swap(index, grandparent);
+            index = grandparent;
+        }
+    }
+
+    fn trickle_down(&mut self, mut index: usize) {
+        let min_level = is_min_level(index);
+        loop {
+            let descendant = match self.extreme_descendant(index, min_level) {
+                Some(descendant) => descendant,
+                None => return,
+            };
+            if !self.in_order(descendant, index, min_level) {
+                return;
+            }
+            self.items.swap(descendant, index);
+            if descendant <= 2 * index + 2 {
+                return;
+            }
+            let parent = parent(descendant);
+            if self.in_order(parent, descendant, min_level) {
+                self.items.swap(parent, descendant);
+            }
+            index = descendant;
+        }
+    }
+
+    // the smallest (or the largest) of the children and grandchildren
+    fn extreme_descendant(&self, index: usize, min_level: bool) -> Option<usize> {
+        let first_child = 2 * index + 1;
+        let first_grandchild = 4 * index + 3;
+        let descendants =
+            (first_child..first_child + 2).chain(first_grandchild..first_grandchild + 4);
+        descendants
+            .take_while(|&descendant| descendant < self.items.len())
+            .fold(None, |extreme, descendant| match extreme {
+                Some(extreme) if !self.in_order(descendant, extreme, min_level) => Some(extreme),
+                _ => Some(descendant),
+            })
+    }
+
+    // whether the item at `index` belongs above the one at `other` on this kind of level
+    fn in_order(&self, index: usize, other: usize, min_level: bool) -> bool {
+        if min_level {
+            self.items[index] < self.items[other]
+        } else {
+            self.items[index] > self.items[other]
+        }
+    }
+}
+
+impl<T: Ord> Default for MinMaxHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MinMaxHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = MinMaxHeap {
+            items: iter.into_iter().collect(),
+        };
+        for index in (0..heap.len() / 2).rev() {
+            heap.trickle_down(index);
+        }
+        heap
+    }
+}
+
+impl<T: Ord> Extend<T> for MinMaxHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shuffled(len: u32) -> Vec<u32> {
+        let mut seed = 42u32;
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (seed >> 16) % (len / 2 + 1)
+            })
+            .collect()
+    }
+
+    fn check_levels(heap: &MinMaxHeap<u32>) {
+        for index in 1..heap.items.len() {
+            let mut ancestor = index;
+            let mut min_level = !is_min_level(index);
+            while ancestor > 0 {
+                ancestor = parent(ancestor);
+                if min_level {
+                    assert!(
+                        heap.items[ancestor] <= heap.items[index],
+                        "{:?} at {}",
+                        heap.items,
+                        index
+                    );
+                } else {
+                    assert!(
+                        heap.items[ancestor] >= heap.items[index],
+                        "{:?} at {}",
+                        heap.items,
+                        index
+                    );
+                }
+                min_level = !min_level;
+            }
+        }
+    }
+
+    #[test]
+    fn empty_heap() {
+        let mut heap: MinMaxHeap<i32> = MinMaxHeap::default();
+
+        assert!(heap.is_empty());
+        assert_eq!(heap.peek_min(), None);
+        assert_eq!(heap.peek_max(), None);
+        assert_eq!(heap.pop_min(), None);
+        assert_eq!(heap.pop_max(), None);
+    }
+
+    #[test]
+    fn single_item_is_both_min_and_max() {
+        let mut heap = MinMaxHeap::new();
+
+        heap.push(1);
+
+        assert_eq!(heap.peek_min(), Some(&1));
+        assert_eq!(heap.peek_max(), Some(&1));
+        assert_eq!(heap.pop_max(), Some(1));
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn peek_both_ends() {
+        let mut heap = MinMaxHeap::new();
+
+        heap.extend(vec![5, 3, 8, 1, 9, 2]);
+
+        assert_eq!(heap.len(), 6);
+        assert_eq!(heap.peek_min(), Some(&1));
+        assert_eq!(heap.peek_max(), Some(&9));
+    }
+
+    #[test]
+    fn pop_min_in_ascending_order() {
+        let mut heap = MinMaxHeap::new();
+        heap.extend(shuffled(100));
+        let mut expected = shuffled(100);
+        expected.sort();
+
+        let mut popped = vec![];
+        while let Some(item) = heap.pop_min() {
+            check_levels(&heap);
+            popped.push(item);
+        }
+
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn pop_max_in_descending_order() {
+        let mut heap = MinMaxHeap::new();
+        heap.extend(shuffled(100));
+        let mut expected = shuffled(100);
+        expected.sort_by(|a, b| b.cmp(a));
+
+        let mut popped = vec![];
+        while let Some(item) = heap.pop_max() {
+            check_levels(&heap);
+            popped.push(item);
+        }
+
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn pops_from_both_ends_interleaved() {
+        let mut heap: MinMaxHeap<u32> = shuffled(51).into_iter().collect();
+        let mut expected = shuffled(51);
+        expected.sort();
+
+        for round in 0..25 {
+            assert_eq!(heap.pop_min(), Some(expected[round]));
+            assert_eq!(heap.pop_max(), Some(expected[50 - round]));
+            check_levels(&heap);
+        }
+
+        assert_eq!(heap.pop_min(), Some(expected[25]));
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn push_keeps_levels_ordered() {
+        let mut heap = MinMaxHeap::new();
+
+        for item in shuffled(200) {
+            heap.push(item);
+            check_levels(&heap);
+        }
+    }
+
+    #[test]
+    fn heapify_keeps_levels_ordered() {
+        for len in 0..40 {
+            let heap: MinMaxHeap<u32> = shuffled(len).into_iter().collect();
+
+            check_levels(&heap);
+        }
+    }
+
+    #[test]
+    fn into_sorted_vec() {
+        let heap: MinMaxHeap<u32> = shuffled(64).into_iter().collect();
+        let mut expected = shuffled(64);
+        expected.sort();
+
+        assert_eq!(heap.into_sorted_vec(), expected);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::index_min_pq::IndexMinPQ;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Copy, Clone)]
+pub struct WeightedEdge {
+    v: usize,
+    w: usize,
+    weight: usize,
+}
+
+impl WeightedEdge {
+    pub fn new(v: usize, w: usize, weight: usize) -> WeightedEdge {
+        WeightedEdge { v, w, weight }
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    pub fn either(&self) -> usize {
+        self.v
+    }
+
+    pub fn other(&self, v: usize) -> usize {
+        if v == self.v {
+            self.w
+        } else {
+            self.v
+        }
+    }
+}
+
+impl Ord for WeightedEdge {
+    fn cmp(&self, other: &WeightedEdge) -> Ordering {
+        if self.weight < other.weight {
+            Ordering::Greater
+        } else if self.weight > other.weight {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
+// by weight like `cmp`, so that `==` agrees with `cmp` returning `Equal`
+impl PartialEq for WeightedEdge {
+    fn eq(&self, other: &WeightedEdge) -> bool {
+        self.weight == other.weight
+    }
+}
+
+impl Eq for WeightedEdge {}
+
+// by weight like `cmp`, `BinaryHeap` sifts with `<=`, a derived one would
+// order edges by their vertices first
+impl PartialOrd for WeightedEdge {
+    fn partial_cmp(&self, other: &WeightedEdge) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Default)]
+pub struct EdgeWeightedGraph {
+    edges: HashMap<usize, Vec<WeightedEdge>>,
+}
+
+impl EdgeWeightedGraph {
+    pub fn vertices(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn edges(&self) -> usize {
+        self.edges.values().fold(0, |acc, v| acc + v.len()) / 2
+    }
+
+    pub fn add_edge(&mut self, edge: WeightedEdge) {
+        let v = edge.either();
+        self.edges.entry(v).or_default().push(edge);
+        self.edges.entry(edge.other(v)).or_default().push(edge);
+    }
+
+    pub fn adjacent_to(&self, v: usize) -> Option<&Vec<WeightedEdge>> {
+        self.edges.get(&v)
+    }
+}
+
+#[derive(Debug)]
+pub struct LazyMst {
+    marked: HashSet<usize>,
+    pq: BinaryHeap<WeightedEdge>,
+    weight: usize,
+}
+
+impl LazyMst {
+    pub fn new(graph: &EdgeWeightedGraph) -> Option<LazyMst> {
+        if graph.vertices() > 0 {
+            let mut lazy_mst = LazyMst {
+                marked: HashSet::default(),
+                pq: BinaryHeap::default(),
+                weight: 0,
+            };
+            let mut mst = VecDeque::default();
+            lazy_mst.visit(graph, *graph.edges.keys().next().unwrap());
+            while let Some(edge) = lazy_mst.pq.pop() {
+                let v = edge.either();
+                let w = edge.other(v);
+                if !lazy_mst.marked.contains(&v) || !lazy_mst.marked.contains(&w) {
+                    mst.push_back(edge);
+                    if !lazy_mst.marked.contains(&v) {
+                        lazy_mst.visit(graph, v);
+                    }
+                    if !lazy_mst.marked.contains(&w) {
+                        lazy_mst.visit(graph, w);
+                    }
+                }
+            }
+            lazy_mst.weight = mst.iter().fold(0, |acc, e| acc + e.weight);
+            Some(lazy_mst)
+        } else {
+            None
+        }
+    }
+
+    fn visit(&mut self, graph: &EdgeWeightedGraph, v: usize) {
+        self.marked.insert(v);
+        if let Some(adj) = graph.adjacent_to(v) {
+            for e in adj {
+                if !self.marked.contains(&e.other(v)) {
+                    self.pq.push(*e);
+                }
+            }
+        }
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+}
+
+fn index_capacity(graph: &EdgeWeightedGraph) -> usize {
+    graph.edges.keys().max().map_or(0, |v| v + 1)
+}
+
+#[derive(Debug)]
+pub struct EagerMst {
+    edge_to: HashMap<usize, WeightedEdge>,
+    weight: usize,
+}
+
+impl EagerMst {
+    pub fn new(graph: &EdgeWeightedGraph) -> Option<EagerMst> {
+        let start = match graph.edges.keys().next() {
+            Some(&v) => v,
+            None => return None,
+        };
+        let mut marked: HashSet<usize> = HashSet::default();
+        let mut edge_to: HashMap<usize, WeightedEdge> = HashMap::default();
+        let mut pq = IndexMinPQ::new(index_capacity(graph));
+        pq.insert(start, 0);
+        while let Some((v, _)) = pq.pop_min() {
+            marked.insert(v);
+            for e in graph.adjacent_to(v).unwrap() {
+                let w = e.other(v);
+                if marked.contains(&w) {
+                    continue;
+                }
+                if !pq.contains(w) {
+                    pq.insert(w, e.weight());
+                    edge_to.insert(w, *e);
+                } else if e.weight() < *pq.key_of(w).unwrap() {
+                    pq.decrease_key(w, e.weight());
+                    edge_to.insert(w, *e);
+                }
+            }
+        }
+        let weight = edge_to.values().fold(0, |acc, e| acc + e.weight());
+        Some(EagerMst { edge_to, weight })
+    }
+
+    pub fn edges(&self) -> Vec<WeightedEdge> {
+        self.edge_to.values().cloned().collect()
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+}
+
+#[derive(Debug)]
+pub struct ShortestPaths {
+    dist_to: HashMap<usize, usize>,
+    edge_to: HashMap<usize, WeightedEdge>,
+}
+
+impl ShortestPaths {
+    pub fn new(graph: &EdgeWeightedGraph, source: usize) -> Option<ShortestPaths> {
+        graph.adjacent_to(source)?;
+        let mut dist_to = HashMap::default();
+        let mut edge_to: HashMap<usize, WeightedEdge> = HashMap::default();
+        let mut pq = IndexMinPQ::new(index_capacity(graph));
+        dist_to.insert(source, 0);
+        pq.insert(source, 0);
+        while let Some((v, dist)) = pq.pop_min() {
+            for e in graph.adjacent_to(v).unwrap() {
+                let w = e.other(v);
+                let through_v = dist + e.weight();
+                let shorter = match dist_to.get(&w) {
+                    Some(&known) => through_v < known,
+                    None => true,
+                };
+                if shorter {
+                    dist_to.insert(w, through_v);
+                    edge_to.insert(w, *e);
+                    if pq.contains(w) {
+                        pq.decrease_key(w, through_v);
+                    } else {
+                        pq.insert(w, through_v);
+                    }
+                }
+            }
+        }
+        Some(ShortestPaths { dist_to, edge_to })
+    }
+
+    pub fn dist_to(&self, v: usize) -> Option<usize> {
+        self.dist_to.get(&v).cloned()
+    }
+
+    pub fn path_to(&self, v: usize) -> Option<Vec<usize>> {
+        if !self.dist_to.contains_key(&v) {
+            return None;
+        }
+        let mut path = VecDeque::default();
+        let mut current = v;
+        path.push_front(current);
+        while let Some(e) = self.edge_to.get(&current) {
+            current = e.other(current);
+            path.push_front(current);
+        }
+        Some(path.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(usize, usize, usize)]) -> EdgeWeightedGraph {
+        let mut graph = EdgeWeightedGraph::default();
+        for &(v, w, weight) in edges {
+            graph.add_edge(WeightedEdge::new(v, w, weight));
+        }
+        graph
+    }
+
+    const CONNECTED: [(usize, usize, usize); 6] = [
+        (0, 1, 4),
+        (0, 2, 1),
+        (2, 1, 2),
+        (1, 3, 5),
+        (2, 3, 8),
+        (3, 4, 3),
+    ];
+
+    #[test]
+    fn edge_is_ordered_and_equal_by_weight_only() {
+        let bigger = WeightedEdge::new(1, 2, 3);
+        let smaller = WeightedEdge::new(2, 3, 1);
+
+        assert_eq!(bigger.cmp(&smaller), Ordering::Less);
+        assert_eq!(bigger.partial_cmp(&smaller), Some(Ordering::Less));
+        assert!(bigger < smaller);
+        assert_eq!(
+            WeightedEdge::new(1, 2, 2).cmp(&WeightedEdge::new(1, 3, 2)),
+            Ordering::Equal
+        );
+        assert_eq!(WeightedEdge::new(1, 2, 2), WeightedEdge::new(1, 3, 2));
+        assert_ne!(WeightedEdge::new(1, 2, 2), WeightedEdge::new(1, 2, 3));
+    }
+
+    #[test]
+    fn other_vertex() {
+        let edge = WeightedEdge::new(1, 2, 1);
+
+        assert_eq!(edge.either(), 1);
+        assert_eq!(edge.other(1), 2);
+        assert_eq!(edge.other(2), 1);
+    }
+
+    #[test]
+    fn count_vertices_and_edges() {
+        let graph = graph(&[(1, 2, 1), (2, 3, 1), (1, 4, 1)]);
+
+        assert_eq!(graph.vertices(), 4);
+        assert_eq!(graph.edges(), 3);
+    }
+
+    #[test]
+    fn no_mst_of_an_empty_graph() {
+        let graph = EdgeWeightedGraph::default();
+
+        assert!(LazyMst::new(&graph).is_none());
+        assert!(EagerMst::new(&graph).is_none());
+    }
+
+    #[test]
+    fn lazy_mst_picks_the_lightest_crossing_edges() {
+        assert_eq!(LazyMst::new(&graph(&CONNECTED)).unwrap().weight(), 11);
+    }
+
+    #[test]
+    fn eager_mst_picks_the_lightest_crossing_edges() {
+        let mst = EagerMst::new(&graph(&CONNECTED)).unwrap();
+
+        assert_eq!(mst.weight(), 11);
+        assert_eq!(mst.edges().len(), 4);
+    }
+
+    #[test]
+    fn eager_mst_of_a_cycle() {
+        let mst = EagerMst::new(&graph(&[(1, 2, 1), (2, 3, 1), (1, 3, 1)])).unwrap();
+
+        assert_eq!(mst.weight(), 2);
+        assert_eq!(mst.edges().len(), 2);
+    }
+
+    #[test]
+    fn no_shortest_paths_from_a_missing_vertex() {
+        assert!(ShortestPaths::new(&graph(&CONNECTED), 5).is_none());
+    }
+
+    #[test]
+    fn shortest_distances_and_paths() {
+        let paths = ShortestPaths::new(&graph(&CONNECTED), 0).unwrap();
+
+        assert_eq!(paths.dist_to(0), Some(0));
+        assert_eq!(paths.dist_to(1), Some(3));
+        assert_eq!(paths.dist_to(3), Some(8));
+        assert_eq!(paths.dist_to(4), Some(11));
+        assert_eq!(paths.path_to(4), Some(vec![0, 2, 1, 3, 4]));
+        assert_eq!(paths.path_to(0), Some(vec![0]));
+    }
+
+    #[test]
+    fn another_component_is_unreachable() {
+        let mut graph = graph(&CONNECTED);
+        graph.add_edge(WeightedEdge::new(7, 8, 1));
+
+        let paths = ShortestPaths::new(&graph, 0).unwrap();
+
+        assert_eq!(paths.dist_to(8), None);
+        assert_eq!(paths.path_to(8), None);
+    }
+}
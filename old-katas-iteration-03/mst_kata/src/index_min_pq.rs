@@ -0,0 +1,261 @@
+// a binary heap of indices in `0..capacity`, each index carries its own key;
+// `positions` maps an index back to its place in the heap, so a key can be
+// changed or removed in O(log n) instead of leaving stale entries behind
+#[derive(Debug)]
+pub struct IndexMinPQ<K> {
+    heap: Vec<usize>,
+    positions: Vec<Option<usize>>,
+    keys: Vec<Option<K>>,
+}
+
+impl<K: Ord> IndexMinPQ<K> {
+    pub fn new(capacity: usize) -> IndexMinPQ<K> {
+        Self {
+            heap: Vec::with_capacity(capacity),
+            positions: vec![None; capacity],
+            keys: (0..capacity).map(|_| None).collect(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.capacity() && self.positions[index].is_some()
+    }
+
+    pub fn key_of(&self, index: usize) -> Option<&K> {
+        if index < self.capacity() {
+            self.keys[index].as_ref()
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, key: K) {
+        assert!(
+            index < self.capacity(),
+            "index {} is out of 0..{}",
+            index,
+            self.capacity()
+        );
+        assert!(
+            !self.contains(index),
+            "index {} is already in the queue",
+            index
+        );
+        let position = self.heap.len();
+        self.heap.push(index);
+        self.positions[index] = Some(position);
+        self.keys[index] = Some(key);
+        self.swim(position);
+    }
+
+    pub fn decrease_key(&mut self, index: usize, key: K) {
+        let position = self.position_of(index);
+        assert!(
+            &key <= self.keys[index].as_ref().unwrap(),
+            "key of index {} can only decrease",
+            index
+        );
+        self.keys[index] = Some(key);
+        self.swim(position);
+    }
+
+    pub fn peek_min(&self) -> Option<(usize, &K)> {
+        self.heap
+            .first()
+            .map(|&index| (index, self.keys[index].as_ref().unwrap()))
+    }
+
+    pub fn pop_min(&mut self) -> Option<(usize, K)> {
+        match self.heap.first() {
+            Some(&index) => self.delete(index).map(|key| (index, key)),
+            None => None,
+        }
+    }
+
+    pub fn delete(&mut self, index: usize) -> Option<K> {
+        if !self.contains(index) {
+            return None;
+        }
+        let position = self.position_of(index);
+        let last = self.heap.len() - 1;
+        self.exchange(position, last);
+        self.heap.pop();
+        self.positions[index] = None;
+        if position < self.heap.len() {
+            self.swim(position);
+            self.sink(position);
+        }
+        self.keys[index].take()
+    }
+
+    fn position_of(&self, index: usize) -> usize {
+        match self.positions.get(index) {
+            Some(&Some(position)) => position,
+            _ => panic!("index {} is not in the queue", index),
+        }
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.keys[self.heap[a]] < self.keys[self.heap[b]]
+    }
+
+    fn exchange(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = Some(a);
+        self.positions[self.heap[b]] = Some(b);
+    }
+
+    fn swim(&mut self, mut position: usize) {
+        while position > 0 && self.less(position, (position - 1) / 2) {
+            self.exchange(position, (position - 1) / 2);
+            position = (position - 1) / 2;
+        }
+    }
+
+    fn sink(&mut self, mut position: usize) {
+        loop {
+            let mut child = 2 * position + 1;
+            if child >= self.heap.len() {
+                return;
+            }
+            if child + 1 < self.heap.len() && self.less(child + 1, child) {
+                child += 1;
+            }
+            if !self.less(child, position) {
+                return;
+            }
+            self.exchange(position, child);
+            position = child;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> IndexMinPQ<usize> {
+        IndexMinPQ::new(10)
+    }
+
+    #[test]
+    fn empty() {
+        let mut pq = queue();
+
+        assert!(pq.is_empty());
+        assert_eq!(pq.capacity(), 10);
+        assert_eq!(pq.pop_min(), None);
+    }
+
+    #[test]
+    fn insert_keys_by_index() {
+        let mut pq = queue();
+
+        pq.insert(3, 30);
+        pq.insert(7, 10);
+
+        assert_eq!(pq.len(), 2);
+        assert!(pq.contains(3));
+        assert!(!pq.contains(5));
+        assert!(!pq.contains(42));
+        assert_eq!(pq.key_of(3), Some(&30));
+        assert_eq!(pq.peek_min(), Some((7, &10)));
+    }
+
+    #[test]
+    fn pop_indices_in_key_order() {
+        let mut pq = queue();
+        for &(index, key) in &[(0, 5), (1, 3), (2, 8), (3, 1), (4, 9), (5, 2)] {
+            pq.insert(index, key);
+        }
+
+        let mut popped = vec![];
+        while let Some(min) = pq.pop_min() {
+            popped.push(min);
+        }
+
+        assert_eq!(popped, vec![(3, 1), (5, 2), (1, 3), (0, 5), (2, 8), (4, 9)]);
+        assert!(!pq.contains(3));
+    }
+
+    #[test]
+    fn decrease_key() {
+        let mut pq = queue();
+        pq.insert(1, 10);
+        pq.insert(2, 20);
+        pq.insert(3, 30);
+
+        pq.decrease_key(3, 5);
+
+        assert_eq!(pq.key_of(3), Some(&5));
+        assert_eq!(pq.pop_min(), Some((3, 5)));
+        assert_eq!(pq.pop_min(), Some((1, 10)));
+    }
+
+    #[test]
+    fn delete() {
+        let mut pq = queue();
+        for &(index, key) in &[(1, 10), (2, 20), (3, 30), (4, 40)] {
+            pq.insert(index, key);
+        }
+
+        assert_eq!(pq.delete(2), Some(20));
+        assert_eq!(pq.delete(2), None);
+
+        assert_eq!(pq.len(), 3);
+        assert_eq!(pq.pop_min(), Some((1, 10)));
+        assert_eq!(pq.pop_min(), Some((3, 30)));
+        assert_eq!(pq.pop_min(), Some((4, 40)));
+    }
+
+    #[test]
+    fn reuse_popped_index() {
+        let mut pq = queue();
+        pq.insert(1, 10);
+        pq.pop_min();
+
+        pq.insert(1, 5);
+
+        assert_eq!(pq.pop_min(), Some((1, 5)));
+    }
+
+    #[test]
+    #[should_panic(expected = "index 1 is already in the queue")]
+    fn insert_same_index_twice() {
+        let mut pq = queue();
+        pq.insert(1, 10);
+        pq.insert(1, 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 10 is out of 0..10")]
+    fn insert_past_capacity() {
+        queue().insert(10, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "key of index 1 can only decrease")]
+    fn increase_key() {
+        let mut pq = queue();
+        pq.insert(1, 10);
+        pq.decrease_key(1, 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 1 is not in the queue")]
+    fn decrease_key_of_missing_index() {
+        queue().decrease_key(1, 10);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod day_01;
+pub mod index_min_pq;
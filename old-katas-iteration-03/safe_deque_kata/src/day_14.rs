@@ -1,4 +1,6 @@
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell, RefMut};
+use std::marker::PhantomData;
+use std::mem;
 use std::rc::Rc;
 
 type Link<T> = Option<Rc<RefCell<Node<T>>>>;
@@ -18,9 +20,98 @@ impl<T> Node<T> {
 pub struct Deque<T> {
     head: Link<T>,
     tail: Link<T>,
+    len: usize,
 }
 
 impl<T> Deque<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn contains(&self, item: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        let mut node = self.head.clone();
+        while let Some(current) = node {
+            if current.borrow().item == *item {
+                return true;
+            }
+            node = current.borrow().next.clone();
+        }
+        false
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        if let Some(other_head) = other.head.take() {
+            match self.tail.take() {
+                Some(tail) => {
+                    other_head.borrow_mut().prev = Some(tail.clone());
+                    tail.borrow_mut().next = Some(other_head);
+                }
+                None => {
+                    self.head = Some(other_head);
+                }
+            }
+            self.tail = other.tail.take();
+            self.len += other.len;
+            other.len = 0;
+        }
+    }
+
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "cannot split off at {} in a deque of length {}", at, self.len);
+        if at == 0 {
+            return mem::take(self);
+        }
+        let mut last = self.head.clone().unwrap();
+        for _ in 1..at {
+            let next = last.borrow().next.clone().unwrap();
+            last = next;
+        }
+        let head = last.borrow_mut().next.take();
+        let tail = match head {
+            Some(ref head) => {
+                head.borrow_mut().prev.take();
+                self.tail.replace(last)
+            }
+            None => None
+        };
+        let len = self.len - at;
+        self.len = at;
+        Self { head, tail, len }
+    }
+
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        let mut node = self.head.clone();
+        while let Some(current) = node {
+            node = current.borrow().next.clone();
+            if keep(&current.borrow().item) {
+                continue;
+            }
+            let mut removed = current.borrow_mut();
+            let prev = removed.prev.take();
+            let next = removed.next.take();
+            match prev {
+                Some(ref prev) => prev.borrow_mut().next = next.clone(),
+                None => self.head = next.clone()
+            }
+            match next {
+                Some(next) => next.borrow_mut().prev = prev,
+                None => self.tail = prev
+            }
+            self.len -= 1;
+        }
+    }
+
     pub fn pop_front(&mut self) -> Option<T> {
         self.head.take().and_then(|old_head| {
             match old_head.borrow_mut().next.take() {
@@ -32,6 +123,7 @@ impl<T> Deque<T> {
                     self.tail.take();
                 }
             }
+            self.len -= 1;
             Rc::try_unwrap(old_head).ok().map(|r| r.into_inner().item)
         })
     }
@@ -48,6 +140,7 @@ impl<T> Deque<T> {
             }
         }
         self.head = Some(new_head);
+        self.len += 1;
     }
 
     pub fn pop_back(&mut self) -> Option<T> {
@@ -61,6 +154,7 @@ impl<T> Deque<T> {
                     self.head.take();
                 }
             }
+            self.len -= 1;
             Rc::try_unwrap(old_tail).ok().map(|r| r.into_inner().item)
         })
     }
@@ -77,12 +171,54 @@ impl<T> Deque<T> {
             }
         }
         self.tail = Some(new_tail);
+        self.len += 1;
+    }
+
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
+        self.head.as_ref().map(|head| Ref::map(head.borrow(), |node| &node.item))
+    }
+
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
+        self.tail.as_ref().map(|tail| Ref::map(tail.borrow(), |node| &node.item))
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.head.as_ref().map(|head| RefMut::map(head.borrow_mut(), |node| &mut node.item))
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.tail.as_ref().map(|tail| RefMut::map(tail.borrow_mut(), |node| &mut node.item))
+    }
+
+    pub fn iter(&self) -> DequeIterRef<'_, T> {
+        DequeIterRef {
+            front: self.head.clone(),
+            back: self.tail.clone(),
+            len: self.len,
+            deque: PhantomData,
+        }
+    }
+
+    pub fn for_each_mut<F: FnMut(&mut T)>(&mut self, mut visit: F) {
+        let mut node = self.head.clone();
+        while let Some(current) = node {
+            visit(&mut current.borrow_mut().item);
+            node = current.borrow().next.clone();
+        }
     }
 }
 
 impl<T> Default for Deque<T> {
     fn default() -> Self {
-        Self { head: None, tail: None }
+        Self { head: None, tail: None, len: 0 }
+    }
+}
+
+impl<T> Extend<T> for Deque<T> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, items: I) {
+        for item in items {
+            self.push_back(item);
+        }
     }
 }
 
@@ -113,6 +249,71 @@ impl<T> DoubleEndedIterator for DequeIter<T> {
     }
 }
 
+impl<'d, T> IntoIterator for &'d Deque<T> {
+    type Item = ItemRef<'d, T>;
+    type IntoIter = DequeIterRef<'d, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// yields ItemRef handles rather than the Ref<'d, T> guards one would expect:
+// a node past the head is only reachable through the guard of the one before
+// it, and that guard can't outlive a call to next, so a Ref to a later item
+// could only be had with unsafe code; the deque stays borrowed for 'd, so no
+// node can be unlinked or popped while a handle is alive
+pub struct DequeIterRef<'d, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    deque: PhantomData<&'d Deque<T>>,
+}
+
+// stands in for Ref<'d, T>, call borrow to get at the item
+pub struct ItemRef<'d, T> {
+    node: Rc<RefCell<Node<T>>>,
+    deque: PhantomData<&'d Deque<T>>,
+}
+
+impl<'d, T> ItemRef<'d, T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.item)
+    }
+}
+
+impl<'d, T> Iterator for DequeIterRef<'d, T> {
+    type Item = ItemRef<'d, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.take().map(|node| {
+            self.len -= 1;
+            self.front = node.borrow().next.clone();
+            ItemRef { node, deque: PhantomData }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'d, T> DoubleEndedIterator for DequeIterRef<'d, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.take().map(|node| {
+            self.len -= 1;
+            self.back = node.borrow().prev.clone();
+            ItemRef { node, deque: PhantomData }
+        })
+    }
+}
+
 impl<T> Drop for Deque<T> {
     fn drop(&mut self) {
         while let Some(_) = self.pop_front() {}
@@ -213,4 +414,195 @@ mod tests {
         assert_eq!(iter.next(), Some(2));
         assert_eq!(iter.next_back(), None);
     }
+
+    fn deque(items: &[i32]) -> Deque<i32> {
+        let mut deque = Deque::default();
+        deque.extend(items.iter().cloned());
+        deque
+    }
+
+    fn items(deque: Deque<i32>) -> Vec<i32> {
+        deque.into_iter().collect()
+    }
+
+    #[test]
+    fn len_and_is_empty() {
+        let mut deque = Deque::default();
+
+        assert!(deque.is_empty());
+        deque.push_back(1);
+        deque.push_front(2);
+        deque.push_back(3);
+        assert_eq!(deque.len(), 3);
+        deque.pop_back();
+        deque.pop_front();
+        assert_eq!(deque.len(), 1);
+        deque.pop_front();
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn append() {
+        let mut deque = deque(&[1, 2]);
+        let mut other = self::deque(&[3, 4]);
+
+        deque.append(&mut other);
+        deque.append(&mut Deque::default());
+
+        assert!(other.is_empty());
+        assert_eq!(other.pop_front(), None);
+        assert_eq!(deque.len(), 4);
+        assert_eq!(items(deque), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn append_to_empty_deque() {
+        let mut deque = Deque::default();
+
+        deque.append(&mut self::deque(&[1, 2]));
+        deque.push_back(3);
+
+        assert_eq!(deque.len(), 3);
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(items(deque), vec![1, 2]);
+    }
+
+    #[test]
+    fn split_off() {
+        for at in 0..=5 {
+            let mut deque = deque(&[0, 1, 2, 3, 4]);
+
+            let mut tail = deque.split_off(at);
+
+            assert_eq!(deque.len(), at);
+            assert_eq!(tail.len(), 5 - at);
+            tail.push_front(-1);
+            deque.push_back(-1);
+            assert_eq!(items(deque), (0..at as i32).chain(Some(-1)).collect::<Vec<_>>());
+            assert_eq!(items(tail), Some(-1).into_iter().chain(at as i32..5).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_past_the_end() {
+        deque(&[1, 2]).split_off(3);
+    }
+
+    #[test]
+    fn clear() {
+        let mut deque = deque(&[1, 2, 3]);
+
+        deque.clear();
+
+        assert!(deque.is_empty());
+        assert_eq!(deque.pop_back(), None);
+    }
+
+    #[test]
+    fn contains() {
+        let deque = deque(&[1, 2, 3]);
+
+        assert!(deque.contains(&2));
+        assert!(!deque.contains(&4));
+    }
+
+    #[test]
+    fn retain() {
+        let mut deque = deque(&[1, 2, 3, 4, 5, 6]);
+
+        deque.retain(|x| x % 2 == 0);
+
+        assert_eq!(deque.len(), 3);
+        assert_eq!(deque.pop_back(), Some(6));
+        assert_eq!(items(deque), vec![2, 4]);
+    }
+
+    #[test]
+    fn retain_frees_removed_items() {
+        let item = Rc::new(());
+        let mut deque = Deque::default();
+        deque.extend(vec![item.clone(), item.clone(), item.clone()]);
+
+        deque.retain(|_| false);
+
+        assert!(deque.is_empty());
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn peek_front_and_back() {
+        let mut deque = deque(&[1, 2, 3]);
+
+        assert_eq!(*deque.peek_front().unwrap(), 1);
+        assert_eq!(*deque.peek_back().unwrap(), 3);
+        *deque.peek_front_mut().unwrap() *= 10;
+        *deque.peek_back_mut().unwrap() *= 10;
+
+        assert_eq!(items(deque), vec![10, 2, 30]);
+    }
+
+    #[test]
+    fn peek_into_empty_deque() {
+        let mut deque: Deque<i32> = Deque::default();
+
+        assert!(deque.peek_front().is_none());
+        assert!(deque.peek_back_mut().is_none());
+    }
+
+    #[test]
+    fn ref_iterator() {
+        let deque = deque(&[1, 2, 3]);
+
+        let items: Vec<i32> = deque.iter().map(|x| *x.borrow()).collect();
+
+        assert_eq!(items, vec![1, 2, 3]);
+        assert_eq!(deque.len(), 3);
+    }
+
+    #[test]
+    fn double_ended_ref_iterator() {
+        let deque = deque(&[1, 2, 3, 4]);
+        let mut iter = (&deque).into_iter();
+
+        assert_eq!(iter.size_hint(), (4, Some(4)));
+        assert_eq!(iter.next().map(|x| *x.borrow()), Some(1));
+        assert_eq!(iter.next_back().map(|x| *x.borrow()), Some(4));
+        assert_eq!(iter.next_back().map(|x| *x.borrow()), Some(3));
+        assert_eq!(iter.next().map(|x| *x.borrow()), Some(2));
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+    }
+
+    #[test]
+    fn ref_iterator_guards_can_be_held_together() {
+        let deque = deque(&[1, 2]);
+
+        let items: Vec<ItemRef<i32>> = deque.iter().rev().collect();
+        let guards: Vec<Ref<i32>> = items.iter().map(ItemRef::borrow).collect();
+
+        assert_eq!(*guards[0] + *guards[1], 3);
+        assert_eq!(*deque.peek_front().unwrap(), 1);
+    }
+
+    #[test]
+    fn ref_iterator_releases_its_handles() {
+        let mut deque = deque(&[1, 2, 3]);
+
+        let middle = deque.iter().nth(1).map(|x| *x.borrow());
+
+        assert_eq!(middle, Some(2));
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_front(), Some(2));
+    }
+
+    #[test]
+    fn for_each_mut() {
+        let mut deque = deque(&[1, 2, 3]);
+
+        deque.for_each_mut(|x| *x *= *x);
+
+        assert_eq!(items(deque), vec![1, 4, 9]);
+    }
 }
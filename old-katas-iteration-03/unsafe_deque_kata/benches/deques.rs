@@ -0,0 +1,119 @@
+use safe_deque_kata::day_14::Deque;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use unsafe_deque_kata::day_14::UnsafeDeque;
+use unsafe_deque_kata::ring_deque::RingDeque;
+
+const ITEMS: u64 = 1_000_000;
+const WINDOW: u64 = 64;
+
+trait Bench: Default {
+    fn push_front(&mut self, item: u64);
+    fn push_back(&mut self, item: u64);
+    fn pop_front(&mut self) -> Option<u64>;
+    fn pop_back(&mut self) -> Option<u64>;
+    fn sum(&self) -> u64;
+}
+
+macro_rules! bench {
+    ($deque:ty, |$d:ident| $sum:expr) => {
+        impl Bench for $deque {
+            fn push_front(&mut self, item: u64) {
+                <$deque>::push_front(self, item)
+            }
+
+            fn push_back(&mut self, item: u64) {
+                <$deque>::push_back(self, item)
+            }
+
+            fn pop_front(&mut self) -> Option<u64> {
+                <$deque>::pop_front(self)
+            }
+
+            fn pop_back(&mut self) -> Option<u64> {
+                <$deque>::pop_back(self)
+            }
+
+            fn sum(&self) -> u64 {
+                let $d = self;
+                $sum
+            }
+        }
+    };
+}
+
+bench!(RingDeque<u64>, |deque| deque.iter().sum());
+bench!(UnsafeDeque<u64>, |deque| deque.into_iter().sum());
+bench!(Deque<u64>, |deque| deque.iter().map(|item| *item.borrow()).sum());
+
+fn queue<D: Bench>() -> Duration {
+    let mut deque = D::default();
+    let start = Instant::now();
+    for item in 0..ITEMS {
+        deque.push_back(black_box(item));
+    }
+    while let Some(item) = deque.pop_front() {
+        black_box(item);
+    }
+    start.elapsed()
+}
+
+fn both_ends<D: Bench>() -> Duration {
+    let mut deque = D::default();
+    let start = Instant::now();
+    for item in 0..ITEMS / 2 {
+        deque.push_front(black_box(item));
+        deque.push_back(black_box(item));
+    }
+    for _ in 0..ITEMS / 2 {
+        black_box(deque.pop_back());
+        black_box(deque.pop_front());
+    }
+    start.elapsed()
+}
+
+fn sliding_window<D: Bench>() -> Duration {
+    let mut deque = D::default();
+    for item in 0..WINDOW {
+        deque.push_back(item);
+    }
+    let start = Instant::now();
+    for item in WINDOW..ITEMS {
+        deque.push_back(black_box(item));
+        black_box(deque.pop_front());
+    }
+    start.elapsed()
+}
+
+fn iterate<D: Bench>() -> Duration {
+    let mut deque = D::default();
+    for item in 0..ITEMS {
+        deque.push_back(item);
+    }
+    let start = Instant::now();
+    black_box(black_box(&deque).sum());
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{:<28} {:>10.2?} total {:>8.1} ns/item",
+        name,
+        elapsed,
+        elapsed.as_nanos() as f64 / ITEMS as f64
+    );
+}
+
+fn run<D: Bench>(name: &str) {
+    report(&format!("{} queue", name), queue::<D>());
+    report(&format!("{} both ends", name), both_ends::<D>());
+    report(&format!("{} sliding window", name), sliding_window::<D>());
+    report(&format!("{} iterate", name), iterate::<D>());
+}
+
+fn main() {
+    println!("{} u64 items", ITEMS);
+    run::<RingDeque<u64>>("ring");
+    run::<UnsafeDeque<u64>>("unsafe linked");
+    run::<Deque<u64>>("safe linked");
+}
@@ -0,0 +1,518 @@
+use crate::day_14::{Handle, UnsafeDeque, UnsafeDequeIterRef};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+type OnEvict<K, V> = Box<dyn FnMut(K, V)>;
+
+// where an `LfuCache` entry lives: its bucket and its node inside that bucket
+type Slot<K, V> = (Handle<Bucket<K, V>>, Handle<(K, V)>);
+
+// the most recently used entry is at the front of `order`, the map holds a handle
+// to every entry's node so a hit moves it to the front without walking the deque
+pub struct LruCache<K, V> {
+    map: HashMap<K, Handle<(K, V)>>,
+    order: UnsafeDeque<(K, V)>,
+    capacity: usize,
+    on_evict: Option<OnEvict<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "cache capacity must be positive");
+        Self {
+            map: HashMap::with_capacity(capacity),
+            order: UnsafeDeque::default(),
+            capacity,
+            on_evict: None,
+        }
+    }
+
+    // called with every entry `put` pushes out to stay within capacity
+    pub fn on_evict<F: FnMut(K, V) + 'static>(mut self, on_evict: F) -> Self {
+        self.on_evict = Some(Box::new(on_evict));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let handle = *self.map.get(key)?;
+        unsafe {
+            self.order.move_to_front(handle);
+            Some(&mut UnsafeDeque::item_mut(handle).1)
+        }
+    }
+
+    // looks the entry up without making it the most recently used
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.map
+            .get(key)
+            .map(|&handle| unsafe { &UnsafeDeque::item(handle).1 })
+    }
+
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.order.peek_back().map(|(key, value)| (key, value))
+    }
+
+    // returns the previous value of `key`, an entry evicted for room goes to `on_evict`
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        if self.map.len() == self.capacity {
+            if let (Some((key, value)), Some(on_evict)) = (self.pop_lru(), self.on_evict.as_mut()) {
+                on_evict(key, value);
+            }
+        }
+        let handle = self.order.push_front_handle((key.clone(), value));
+        self.map.insert(key, handle);
+        None
+    }
+
+    pub fn pop(&mut self, key: &K) -> Option<V> {
+        let handle = self.map.remove(key)?;
+        Some(unsafe { self.order.remove(handle) }.1)
+    }
+
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let handle = self.order.back_handle()?;
+        // the map lets go of the handle before the node is freed, by the key still
+        // inside it, or by the handle itself if the key no longer hashes to its entry
+        let key = unsafe { &UnsafeDeque::item(handle).0 };
+        if self.map.get(key) == Some(&handle) {
+            self.map.remove(key);
+        } else {
+            self.map.retain(|_, entry| *entry != handle);
+        }
+        Some(unsafe { self.order.remove(handle) })
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+    }
+
+    // from the most to the least recently used
+    pub fn iter(&self) -> LruIter<'_, K, V> {
+        LruIter {
+            entries: (&self.order).into_iter(),
+        }
+    }
+}
+
+pub struct LruIter<'c, K, V> {
+    entries: UnsafeDequeIterRef<'c, (K, V)>,
+}
+
+impl<'c, K, V> Iterator for LruIter<'c, K, V> {
+    type Item = (&'c K, &'c V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(key, value)| (key, value))
+    }
+}
+
+struct Bucket<K, V> {
+    frequency: usize,
+    entries: UnsafeDeque<(K, V)>,
+}
+
+impl<K, V> Bucket<K, V> {
+    fn new(frequency: usize) -> Self {
+        Self {
+            frequency,
+            entries: UnsafeDeque::default(),
+        }
+    }
+}
+
+// entries are grouped into buckets of the same use count, the most used bucket is at
+// the front of `buckets` and each bucket keeps its most recently used entry at the
+// front, so the entry to evict is always at the back of the back bucket
+pub struct LfuCache<K, V> {
+    map: HashMap<K, Slot<K, V>>,
+    buckets: UnsafeDeque<Bucket<K, V>>,
+    capacity: usize,
+    on_evict: Option<OnEvict<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "cache capacity must be positive");
+        Self {
+            map: HashMap::with_capacity(capacity),
+            buckets: UnsafeDeque::default(),
+            capacity,
+            on_evict: None,
+        }
+    }
+
+    // called with every entry `put` pushes out to stay within capacity
+    pub fn on_evict<F: FnMut(K, V) + 'static>(mut self, on_evict: F) -> Self {
+        self.on_evict = Some(Box::new(on_evict));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn frequency(&self, key: &K) -> Option<usize> {
+        self.map
+            .get(key)
+            .map(|&(bucket, _)| unsafe { UnsafeDeque::item(bucket).frequency })
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let handles = self.map.get_mut(key)?;
+        let (bucket, entry) = unsafe { Self::touch(&mut self.buckets, handles.0, handles.1) };
+        *handles = (bucket, entry);
+        Some(unsafe { &mut UnsafeDeque::item_mut(entry).1 })
+    }
+
+    // looks the entry up without counting it as a use
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.map
+            .get(key)
+            .map(|&(_, entry)| unsafe { &UnsafeDeque::item(entry).1 })
+    }
+
+    pub fn peek_lfu(&self) -> Option<(&K, &V)> {
+        let bucket = self.buckets.peek_back()?;
+        bucket.entries.peek_back().map(|(key, value)| (key, value))
+    }
+
+    // returns the previous value of `key`, an entry evicted for room goes to `on_evict`
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        if self.map.len() == self.capacity {
+            if let (Some((key, value)), Some(on_evict)) = (self.pop_lfu(), self.on_evict.as_mut()) {
+                on_evict(key, value);
+            }
+        }
+        let bucket = match self.buckets.back_handle() {
+            Some(bucket) if unsafe { UnsafeDeque::item(bucket).frequency } == 1 => bucket,
+            _ => self.buckets.push_back_handle(Bucket::new(1)),
+        };
+        let entry = unsafe { UnsafeDeque::item_mut(bucket) }
+            .entries
+            .push_front_handle((key.clone(), value));
+        self.map.insert(key, (bucket, entry));
+        None
+    }
+
+    pub fn pop(&mut self, key: &K) -> Option<V> {
+        let (bucket, entry) = self.map.remove(key)?;
+        unsafe {
+            let (_, value) = UnsafeDeque::item_mut(bucket).entries.remove(entry);
+            self.remove_if_empty(bucket);
+            Some(value)
+        }
+    }
+
+    // the least frequently used entry, the least recently used one among equals
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        let bucket = self.buckets.back_handle()?;
+        unsafe {
+            let entry = UnsafeDeque::item(bucket).entries.back_handle()?;
+            // same as in `LruCache::pop_lru`, the map goes first
+            let key = &UnsafeDeque::item(entry).0;
+            if self.map.get(key).map(|&(_, found)| found) == Some(entry) {
+                self.map.remove(key);
+            } else {
+                self.map.retain(|_, &mut (_, found)| found != entry);
+            }
+            let item = UnsafeDeque::item_mut(bucket).entries.remove(entry);
+            self.remove_if_empty(bucket);
+            Some(item)
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.buckets.clear();
+    }
+
+    // from the most to the least frequently used, the most recent first among equals
+    pub fn iter(&self) -> LfuIter<'_, K, V> {
+        LfuIter {
+            buckets: (&self.buckets).into_iter(),
+            entries: None,
+        }
+    }
+
+    // moves the entry one use up, into the bucket in front of its own
+    unsafe fn touch(
+        buckets: &mut UnsafeDeque<Bucket<K, V>>,
+        bucket: Handle<Bucket<K, V>>,
+        entry: Handle<(K, V)>,
+    ) -> Slot<K, V> {
+        let frequency = UnsafeDeque::item(bucket).frequency + 1;
+        let target = match UnsafeDeque::prev_handle(bucket) {
+            Some(prev) if UnsafeDeque::item(prev).frequency == frequency => prev,
+            _ => buckets.insert_before_handle(bucket, Bucket::new(frequency)),
+        };
+        let item = UnsafeDeque::item_mut(bucket).entries.remove(entry);
+        let entry = UnsafeDeque::item_mut(target)
+            .entries
+            .push_front_handle(item);
+        if UnsafeDeque::item(bucket).entries.is_empty() {
+            buckets.remove(bucket);
+        }
+        (target, entry)
+    }
+
+    unsafe fn remove_if_empty(&mut self, bucket: Handle<Bucket<K, V>>) {
+        if UnsafeDeque::item(bucket).entries.is_empty() {
+            self.buckets.remove(bucket);
+        }
+    }
+}
+
+pub struct LfuIter<'c, K, V> {
+    buckets: UnsafeDequeIterRef<'c, Bucket<K, V>>,
+    entries: Option<UnsafeDequeIterRef<'c, (K, V)>>,
+}
+
+impl<'c, K, V> Iterator for LfuIter<'c, K, V> {
+    type Item = (&'c K, &'c V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, value)) = self.entries.as_mut().and_then(|entries| entries.next()) {
+                return Some((key, value));
+            }
+            self.entries = Some((&self.buckets.next()?.entries).into_iter());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Evicted<K, V> = Rc<RefCell<Vec<(K, V)>>>;
+
+    fn evictions<K: 'static, V: 'static>() -> (Evicted<K, V>, impl FnMut(K, V)) {
+        let evicted = Rc::new(RefCell::new(vec![]));
+        let sink = evicted.clone();
+        (evicted, move |key, value| {
+            sink.borrow_mut().push((key, value))
+        })
+    }
+
+    fn lru_keys(cache: &LruCache<&'static str, i32>) -> Vec<&'static str> {
+        cache.iter().map(|(key, _)| *key).collect()
+    }
+
+    fn lfu_keys(cache: &LfuCache<&'static str, i32>) -> Vec<&'static str> {
+        cache.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let (evicted, on_evict) = evictions();
+        let mut cache = LruCache::new(2).on_evict(on_evict);
+
+        cache.put("a", 1);
+        cache.put("b", 2);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        cache.put("c", 3);
+
+        assert_eq!(*evicted.borrow(), vec![("b", 2)]);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(lru_keys(&cache), vec!["c", "a"]);
+    }
+
+    #[test]
+    fn lru_put_replaces_value() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        cache.put("b", 2);
+
+        assert_eq!(cache.put("a", 10), Some(1));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(lru_keys(&cache), vec!["a", "b"]);
+        assert_eq!(cache.peek_lru(), Some((&"b", &2)));
+    }
+
+    #[test]
+    fn lru_peek_keeps_recency() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        cache.put("b", 2);
+
+        assert_eq!(cache.peek(&"a"), Some(&1));
+        cache.put("c", 3);
+
+        assert!(!cache.contains(&"a"));
+        assert_eq!(lru_keys(&cache), vec!["c", "b"]);
+    }
+
+    #[test]
+    fn lru_pop_entries() {
+        let (evicted, on_evict) = evictions();
+        let mut cache = LruCache::new(3).on_evict(on_evict);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        *cache.get_mut(&"a").unwrap() += 10;
+
+        assert_eq!(cache.pop(&"b"), Some(2));
+        assert_eq!(cache.pop(&"b"), None);
+        assert_eq!(cache.pop_lru(), Some(("c", 3)));
+        assert_eq!(cache.pop_lru(), Some(("a", 11)));
+        assert_eq!(cache.pop_lru(), None);
+        assert!(cache.is_empty());
+        assert!(evicted.borrow().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be positive")]
+    fn lru_needs_capacity() {
+        LruCache::<i32, i32>::new(0);
+    }
+
+    #[test]
+    fn lfu_evicts_least_frequently_used() {
+        let (evicted, on_evict) = evictions();
+        let mut cache = LfuCache::new(2).on_evict(on_evict);
+
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.get(&"a");
+        cache.get(&"b");
+        cache.get(&"a");
+        cache.put("c", 3);
+
+        assert_eq!(*evicted.borrow(), vec![("b", 2)]);
+        assert_eq!(cache.frequency(&"a"), Some(3));
+        assert_eq!(cache.frequency(&"c"), Some(1));
+        assert_eq!(lfu_keys(&cache), vec!["a", "c"]);
+    }
+
+    #[test]
+    fn lfu_breaks_ties_by_recency() {
+        let (evicted, on_evict) = evictions();
+        let mut cache = LfuCache::new(3).on_evict(on_evict);
+
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        cache.get(&"b");
+        cache.get(&"a");
+        assert_eq!(cache.peek_lfu(), Some((&"c", &3)));
+        cache.put("d", 4);
+        cache.put("e", 5);
+
+        assert_eq!(*evicted.borrow(), vec![("c", 3), ("d", 4)]);
+        assert_eq!(lfu_keys(&cache), vec!["a", "b", "e"]);
+    }
+
+    #[test]
+    fn lfu_put_counts_as_use() {
+        let mut cache = LfuCache::new(2);
+        cache.put("a", 1);
+
+        assert_eq!(cache.put("a", 10), Some(1));
+        assert_eq!(cache.peek(&"a"), Some(&10));
+
+        assert_eq!(cache.frequency(&"a"), Some(2));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn lfu_pop_entries() {
+        let mut cache = LfuCache::new(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        *cache.get_mut(&"c").unwrap() += 10;
+
+        assert_eq!(cache.pop(&"b"), Some(2));
+        assert_eq!(cache.pop(&"b"), None);
+        assert_eq!(cache.pop_lfu(), Some(("a", 1)));
+        assert_eq!(cache.pop_lfu(), Some(("c", 13)));
+        assert_eq!(cache.pop_lfu(), None);
+        assert!(cache.is_empty());
+        assert!(cache.iter().next().is_none());
+    }
+
+    #[test]
+    fn lfu_matches_a_naive_cache() {
+        let (evicted, on_evict) = evictions();
+        let mut cache = LfuCache::new(6).on_evict(on_evict);
+        // (key, uses, last use)
+        let mut naive: Vec<(u32, usize, usize)> = vec![];
+        let mut seed = 11u32;
+
+        for step in 0..if cfg!(miri) { 300 } else { 10_000 } {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let key = (seed >> 16) % 12;
+            match naive.iter_mut().find(|(k, _, _)| *k == key) {
+                Some(entry) => {
+                    entry.1 += 1;
+                    entry.2 = step;
+                    assert_eq!(cache.get(&key), Some(&key));
+                }
+                None => {
+                    let expected = if naive.len() == 6 {
+                        let at = (0..naive.len())
+                            .min_by_key(|&at| (naive[at].1, naive[at].2))
+                            .unwrap();
+                        Some(naive.remove(at).0)
+                    } else {
+                        None
+                    };
+                    naive.push((key, 1, step));
+                    cache.put(key, key);
+                    assert_eq!(evicted.borrow_mut().pop().map(|(key, _)| key), expected);
+                }
+            }
+            naive.sort_by_key(|&(_, uses, last)| std::cmp::Reverse((uses, last)));
+            assert_eq!(
+                cache.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+                naive.iter().map(|&(key, _, _)| key).collect::<Vec<_>>()
+            );
+        }
+    }
+}
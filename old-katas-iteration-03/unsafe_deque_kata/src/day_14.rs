@@ -1,8 +1,13 @@
+use std::mem;
 use std::ptr::NonNull;
 
 type Link<T> = Option<NonNull<Node<T>>>;
 
-struct Node<T> {
+// a pointer to a node that stays valid until the node is removed from its deque,
+// lets the caches find an item without walking the deque
+pub(crate) type Handle<T> = NonNull<Node<T>>;
+
+pub(crate) struct Node<T> {
     item: T,
     next: Link<T>,
     prev: Link<T>,
@@ -14,13 +19,76 @@ impl<T> Node<T> {
     }
 }
 
-#[derive(Default)]
 pub struct UnsafeDeque<T> {
     head: Link<T>,
     tail: Link<T>,
+    len: usize,
 }
 
 impl<T> UnsafeDeque<T> {
+    fn empty() -> Self {
+        Self { head: None, tail: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::empty();
+    }
+
+    pub fn contains(&self, item: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.into_iter().any(|x| x == item)
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        if let (Some(first), Some(last)) = (other.head.take(), other.tail.take()) {
+            unsafe { self.link(self.tail, first, last, None) }
+            self.len += mem::replace(&mut other.len, 0);
+        }
+    }
+
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "cannot split off at {} in a deque of length {}", at, self.len);
+        let len = self.len;
+        let mut cursor = if at <= len / 2 {
+            let mut cursor = self.cursor_front_mut();
+            for _ in 0..at {
+                cursor.move_next();
+            }
+            cursor
+        } else {
+            let mut cursor = self.cursor_back_mut();
+            cursor.move_next();
+            for _ in at..len {
+                cursor.move_prev();
+            }
+            cursor
+        };
+        let mut before = cursor.split_before();
+        mem::swap(&mut before, self);
+        before
+    }
+
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        let mut cursor = self.cursor_front_mut();
+        while let Some(item) = cursor.current() {
+            if keep(item) {
+                cursor.move_next();
+            } else {
+                cursor.remove_current();
+            }
+        }
+    }
+
     pub fn pop_front(&mut self) -> Option<T> {
         self.head.take().map(|mut old_head| unsafe {
             match old_head.as_mut().next.take() {
@@ -30,6 +98,7 @@ impl<T> UnsafeDeque<T> {
                 }
                 None => { self.tail.take(); }
             }
+            self.len -= 1;
             Box::from_raw(old_head.as_ptr()).item
         })
     }
@@ -44,6 +113,7 @@ impl<T> UnsafeDeque<T> {
                 }
             }
             self.head = Some(new_head);
+            self.len += 1;
         }
     }
 
@@ -56,7 +126,8 @@ impl<T> UnsafeDeque<T> {
                 }
                 None => { self.head.take(); }
             }
-             Box::from_raw(old_tail.as_ptr()).item
+            self.len -= 1;
+            Box::from_raw(old_tail.as_ptr()).item
         })
     }
 
@@ -70,6 +141,7 @@ impl<T> UnsafeDeque<T> {
                 }
             }
             self.tail = Some(new_tail);
+            self.len += 1;
         }
     }
 
@@ -88,6 +160,269 @@ impl<T> UnsafeDeque<T> {
     pub fn peek_back_mut(&mut self) -> Option<&mut T> {
         self.tail.as_mut().map(|tail| unsafe { &mut tail.as_mut().item })
     }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { index: 0, current: self.head, deque: self }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { index: self.len.saturating_sub(1), current: self.tail, deque: self }
+    }
+
+    pub(crate) fn back_handle(&self) -> Option<Handle<T>> {
+        self.tail
+    }
+
+    pub(crate) fn push_front_handle(&mut self, item: T) -> Handle<T> {
+        let node = NonNull::from(Box::leak(Node::new(item)));
+        unsafe { self.link(None, node, node, self.head) }
+        self.len += 1;
+        node
+    }
+
+    pub(crate) fn push_back_handle(&mut self, item: T) -> Handle<T> {
+        let node = NonNull::from(Box::leak(Node::new(item)));
+        unsafe { self.link(self.tail, node, node, None) }
+        self.len += 1;
+        node
+    }
+
+    // the handles passed to the functions below must point into this deque
+
+    pub(crate) unsafe fn insert_before_handle(&mut self, handle: Handle<T>, item: T) -> Handle<T> {
+        let node = NonNull::from(Box::leak(Node::new(item)));
+        self.link(handle.as_ref().prev, node, node, Some(handle));
+        self.len += 1;
+        node
+    }
+
+    pub(crate) unsafe fn prev_handle(handle: Handle<T>) -> Option<Handle<T>> {
+        handle.as_ref().prev
+    }
+
+    pub(crate) unsafe fn item<'n>(handle: Handle<T>) -> &'n T {
+        &(*handle.as_ptr()).item
+    }
+
+    pub(crate) unsafe fn item_mut<'n>(handle: Handle<T>) -> &'n mut T {
+        &mut (*handle.as_ptr()).item
+    }
+
+    pub(crate) unsafe fn move_to_front(&mut self, handle: Handle<T>) {
+        if self.head != Some(handle) {
+            self.unlink(handle);
+            self.link(None, handle, handle, self.head);
+        }
+    }
+
+    pub(crate) unsafe fn remove(&mut self, handle: Handle<T>) -> T {
+        self.unlink(handle);
+        self.len -= 1;
+        Box::from_raw(handle.as_ptr()).item
+    }
+
+    unsafe fn unlink(&mut self, handle: Handle<T>) {
+        let (prev, next) = (handle.as_ref().prev, handle.as_ref().next);
+        match prev {
+            Some(mut prev) => prev.as_mut().next = next,
+            None => self.head = next
+        }
+        match next {
+            Some(mut next) => next.as_mut().prev = prev,
+            None => self.tail = prev
+        }
+    }
+
+    unsafe fn link(&mut self, prev: Link<T>, mut first: NonNull<Node<T>>, mut last: NonNull<Node<T>>, next: Link<T>) {
+        first.as_mut().prev = prev;
+        last.as_mut().next = next;
+        match prev {
+            Some(mut prev) => prev.as_mut().next = Some(first),
+            None => self.head = Some(first)
+        }
+        match next {
+            Some(mut next) => next.as_mut().prev = Some(last),
+            None => self.tail = Some(last)
+        }
+    }
+}
+
+impl<T> Default for UnsafeDeque<T> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> Drop for UnsafeDeque<T> {
+    fn drop(&mut self) {
+        // keeps freeing the rest of the nodes if dropping an item panics
+        struct Guard<'d, T>(&'d mut UnsafeDeque<T>);
+
+        impl<'d, T> Drop for Guard<'d, T> {
+            fn drop(&mut self) {
+                while self.0.pop_front().is_some() {}
+            }
+        }
+
+        while let Some(item) = self.pop_front() {
+            let guard = Guard(self);
+            drop(item);
+            mem::forget(guard);
+        }
+    }
+}
+
+// `current` is `None` between the tail and the head, so moving past either
+// end wraps around through that empty position like `std::collections::LinkedList`;
+// `index` of that position is the length of the deque
+pub struct CursorMut<'d, T> {
+    index: usize,
+    current: Link<T>,
+    deque: &'d mut UnsafeDeque<T>,
+}
+
+impl<'d, T> CursorMut<'d, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(current) => {
+                self.current = unsafe { current.as_ref().next };
+                self.index += 1;
+            }
+            None => {
+                self.current = self.deque.head;
+                self.index = 0;
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(current) => {
+                self.current = unsafe { current.as_ref().prev };
+                self.index = self.index.checked_sub(1).unwrap_or(self.deque.len);
+            }
+            None => {
+                self.current = self.deque.tail;
+                self.index = self.deque.len.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.as_mut().map(|current| unsafe { &mut current.as_mut().item })
+    }
+
+    pub fn insert_before(&mut self, item: T) {
+        let node = NonNull::from(Box::leak(Node::new(item)));
+        unsafe {
+            match self.current {
+                Some(current) => self.deque.link(current.as_ref().prev, node, node, Some(current)),
+                None => self.deque.link(self.deque.tail, node, node, None)
+            }
+        }
+        self.deque.len += 1;
+        self.index += 1;
+    }
+
+    pub fn insert_after(&mut self, item: T) {
+        let node = NonNull::from(Box::leak(Node::new(item)));
+        unsafe {
+            match self.current {
+                Some(current) => self.deque.link(Some(current), node, node, current.as_ref().next),
+                None => self.deque.link(None, node, node, self.deque.head)
+            }
+        }
+        self.deque.len += 1;
+        if self.current.is_none() {
+            self.index += 1;
+        }
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        self.current.map(|current| unsafe {
+            let node = Box::from_raw(current.as_ptr());
+            match node.prev {
+                Some(mut prev) => prev.as_mut().next = node.next,
+                None => self.deque.head = node.next
+            }
+            match node.next {
+                Some(mut next) => next.as_mut().prev = node.prev,
+                None => self.deque.tail = node.prev
+            }
+            self.current = node.next;
+            self.deque.len -= 1;
+            node.item
+        })
+    }
+
+    pub fn split_after(&mut self) -> UnsafeDeque<T> {
+        match self.current {
+            Some(mut current) => unsafe {
+                match current.as_mut().next.take() {
+                    Some(mut head) => {
+                        head.as_mut().prev = None;
+                        let tail = self.deque.tail.replace(current);
+                        let len = mem::replace(&mut self.deque.len, self.index + 1) - self.index - 1;
+                        UnsafeDeque { head: Some(head), tail, len }
+                    }
+                    None => UnsafeDeque::empty()
+                }
+            },
+            None => {
+                self.index = 0;
+                mem::take(self.deque)
+            }
+        }
+    }
+
+    pub fn split_before(&mut self) -> UnsafeDeque<T> {
+        match self.current {
+            Some(mut current) => unsafe {
+                match current.as_mut().prev.take() {
+                    Some(mut tail) => {
+                        tail.as_mut().next = None;
+                        let head = self.deque.head.replace(current);
+                        let len = mem::replace(&mut self.index, 0);
+                        self.deque.len -= len;
+                        UnsafeDeque { head, tail: Some(tail), len }
+                    }
+                    None => UnsafeDeque::empty()
+                }
+            },
+            None => {
+                self.index = 0;
+                mem::take(self.deque)
+            }
+        }
+    }
+
+    pub fn splice_after(&mut self, mut other: UnsafeDeque<T>) {
+        if let (Some(first), Some(last)) = (other.head.take(), other.tail.take()) {
+            unsafe {
+                match self.current {
+                    Some(current) => self.deque.link(Some(current), first, last, current.as_ref().next),
+                    None => self.deque.link(None, first, last, self.deque.head)
+                }
+            }
+            let len = mem::replace(&mut other.len, 0);
+            self.deque.len += len;
+            if self.current.is_none() {
+                self.index += len;
+            }
+        }
+    }
+}
+
+impl<T> Extend<T> for UnsafeDeque<T> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, items: I) {
+        for item in items {
+            self.push_back(item);
+        }
+    }
 }
 
 impl<T> IntoIterator for UnsafeDeque<T> {
@@ -332,6 +667,280 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    fn deque(items: &[i32]) -> UnsafeDeque<i32> {
+        let mut deque = UnsafeDeque::default();
+        for &item in items {
+            deque.push_back(item);
+        }
+        deque
+    }
+
+    fn items(deque: UnsafeDeque<i32>) -> Vec<i32> {
+        deque.into_iter().collect()
+    }
+
+    #[test]
+    fn cursor_moves_through_the_empty_position() {
+        let mut deque = deque(&[1, 2, 3]);
+        let mut cursor = deque.cursor_front_mut();
+
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 3));
+    }
+
+    #[test]
+    fn cursor_edits_current_item() {
+        let mut deque = deque(&[1, 2, 3]);
+        let mut cursor = deque.cursor_back_mut();
+
+        cursor.move_prev();
+        if let Some(x) = cursor.current() {
+            *x *= 10;
+        }
+
+        assert_eq!(items(deque), vec![1, 20, 3]);
+    }
+
+    #[test]
+    fn cursor_inserts_in_the_middle() {
+        let mut deque = deque(&[1, 4]);
+        let mut cursor = deque.cursor_front_mut();
+
+        cursor.insert_after(3);
+        cursor.insert_after(2);
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.insert_before(5);
+        cursor.insert_before(6);
+        cursor.insert_after(0);
+
+        assert_eq!(items(deque), vec![0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn cursor_inserts_into_empty_deque() {
+        let mut deque = UnsafeDeque::default();
+        let mut cursor = deque.cursor_front_mut();
+
+        cursor.insert_before(2);
+        cursor.insert_before(3);
+        cursor.insert_after(1);
+
+        assert_eq!(deque.peek_front(), Some(&1));
+        assert_eq!(deque.peek_back(), Some(&3));
+        assert_eq!(items(deque), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn cursor_removes_current_item() {
+        let mut deque = deque(&[1, 2, 3]);
+        let mut cursor = deque.cursor_front_mut();
+
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(1));
+
+        assert_eq!(deque.peek_front(), None);
+        assert_eq!(deque.peek_back(), None);
+    }
+
+    #[test]
+    fn cursor_splits_deque() {
+        let mut deque = deque(&[1, 2, 3, 4, 5]);
+        let mut cursor = deque.cursor_front_mut();
+
+        cursor.move_next();
+        let after = cursor.split_after();
+        let before = cursor.split_before();
+
+        assert_eq!(items(before), vec![1]);
+        assert_eq!(items(after), vec![3, 4, 5]);
+        assert_eq!(deque.peek_back(), Some(&2));
+        assert_eq!(items(deque), vec![2]);
+    }
+
+    #[test]
+    fn cursor_splits_at_the_ends() {
+        let mut deque = deque(&[1, 2]);
+        let mut cursor = deque.cursor_back_mut();
+
+        assert_eq!(items(cursor.split_after()), vec![]);
+        cursor.move_next();
+        assert_eq!(items(cursor.split_before()), vec![1, 2]);
+        assert_eq!(deque.peek_front(), None);
+    }
+
+    #[test]
+    fn cursor_splices_deque() {
+        let mut deque = deque(&[1, 5]);
+        let mut cursor = deque.cursor_front_mut();
+
+        cursor.splice_after(self::deque(&[2, 3, 4]));
+        cursor.move_prev();
+        cursor.splice_after(self::deque(&[0]));
+        cursor.splice_after(UnsafeDeque::default());
+        cursor.move_prev();
+        cursor.splice_after(self::deque(&[6, 7]));
+
+        assert_eq!(deque.peek_back(), Some(&7));
+        assert_eq!(items(deque), vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn drop_frees_remaining_items() {
+        let item = std::rc::Rc::new(());
+        let mut deque = UnsafeDeque::default();
+
+        deque.push_back(item.clone());
+        deque.push_back(item.clone());
+        drop(deque);
+
+        assert_eq!(std::rc::Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn cursor_keeps_len_and_index() {
+        let mut deque = deque(&[1, 2, 3]);
+        let mut cursor = deque.cursor_back_mut();
+
+        assert_eq!(cursor.index(), Some(2));
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        cursor.insert_before(4);
+        cursor.splice_after(self::deque(&[-1, 0]));
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(5));
+        cursor.remove_current();
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.split_before().len(), 4);
+        assert_eq!(cursor.index(), Some(0));
+
+        assert_eq!(deque.len(), 1);
+        assert_eq!(items(deque), vec![3]);
+    }
+
+    #[test]
+    fn len_and_is_empty() {
+        let mut deque = UnsafeDeque::default();
+
+        assert!(deque.is_empty());
+        deque.push_back(1);
+        deque.push_front(2);
+        deque.push_back(3);
+        assert_eq!(deque.len(), 3);
+        deque.pop_back();
+        deque.pop_front();
+        assert_eq!(deque.len(), 1);
+        deque.pop_front();
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn append() {
+        let mut deque = deque(&[1, 2]);
+        let mut other = self::deque(&[3, 4]);
+
+        deque.append(&mut other);
+        deque.append(&mut UnsafeDeque::default());
+
+        assert!(other.is_empty());
+        assert_eq!(other.pop_front(), None);
+        assert_eq!(deque.len(), 4);
+        assert_eq!(deque.peek_back(), Some(&4));
+        assert_eq!(items(deque), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn append_to_empty_deque() {
+        let mut deque = UnsafeDeque::default();
+
+        deque.append(&mut self::deque(&[1, 2]));
+
+        assert_eq!(deque.len(), 2);
+        assert_eq!(items(deque), vec![1, 2]);
+    }
+
+    #[test]
+    fn split_off() {
+        for at in 0..=5 {
+            let mut deque = deque(&[0, 1, 2, 3, 4]);
+
+            let tail = deque.split_off(at);
+
+            assert_eq!(deque.len(), at);
+            assert_eq!(tail.len(), 5 - at);
+            assert_eq!(items(deque), (0..at as i32).collect::<Vec<_>>());
+            assert_eq!(items(tail), (at as i32..5).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_past_the_end() {
+        deque(&[1, 2]).split_off(3);
+    }
+
+    #[test]
+    fn clear() {
+        let mut deque = deque(&[1, 2, 3]);
+
+        deque.clear();
+
+        assert!(deque.is_empty());
+        assert_eq!(deque.peek_front(), None);
+        assert_eq!(deque.peek_back(), None);
+    }
+
+    #[test]
+    fn contains() {
+        let deque = deque(&[1, 2, 3]);
+
+        assert!(deque.contains(&2));
+        assert!(!deque.contains(&4));
+    }
+
+    #[test]
+    fn retain() {
+        let mut deque = deque(&[1, 2, 3, 4, 5, 6]);
+
+        deque.retain(|x| x % 2 == 0);
+
+        assert_eq!(deque.len(), 3);
+        assert_eq!(deque.peek_front(), Some(&2));
+        assert_eq!(deque.peek_back(), Some(&6));
+        assert_eq!(items(deque), vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn extend() {
+        let mut deque = deque(&[1]);
+
+        deque.extend(vec![2, 3]);
+
+        assert_eq!(deque.len(), 3);
+        assert_eq!(items(deque), vec![1, 2, 3]);
+    }
+
     #[test]
     fn mut_ref_iterator() {
         let mut deque = UnsafeDeque::default();
@@ -0,0 +1,461 @@
+use std::cell::Cell;
+use std::marker::{PhantomData, PhantomPinned};
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// every list gets its own id, so an item knows which list, if any, it is linked into
+static NEXT_LIST_ID: AtomicUsize = AtomicUsize::new(1);
+
+const UNLINKED: usize = 0;
+
+// the link fields embedded into a user struct, one `Links` per list the struct can be in
+pub struct Links<T> {
+    list: Cell<usize>,
+    next: Cell<Option<NonNull<T>>>,
+    prev: Cell<Option<NonNull<T>>>,
+    _pinned: PhantomPinned,
+}
+
+impl<T> Links<T> {
+    pub const fn new() -> Self {
+        Self {
+            list: Cell::new(UNLINKED),
+            next: Cell::new(None),
+            prev: Cell::new(None),
+            _pinned: PhantomPinned,
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.list.get() != UNLINKED
+    }
+
+    fn unlink(&self) {
+        self.list.set(UNLINKED);
+        self.next.set(None);
+        self.prev.set(None);
+    }
+}
+
+impl<T> Default for Links<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Picks the `Links` field a `List<T, Tag>` threads through; a struct that lives in
+/// several lists at once implements `Linked<Tag>` once per list with a different tag.
+///
+/// # Safety
+///
+/// `links` must return the same field of `self` on every call, and no two tags of
+/// the same type may share a field.
+pub unsafe trait Linked<Tag = ()>: Sized {
+    fn links(&self) -> &Links<Self>;
+}
+
+// borrows the items for `'a`, the tag only picks the `Links` field and is never stored
+type ListMarker<'a, T, Tag> = PhantomData<(&'a T, fn(Tag))>;
+
+// the list never owns its items, it borrows them for `'a`, so every item outlives
+// the list and, being pinned, stays at the address the neighbours point to
+pub struct List<'a, T: Linked<Tag>, Tag = ()> {
+    id: usize,
+    head: Option<NonNull<T>>,
+    tail: Option<NonNull<T>>,
+    len: usize,
+    marker: ListMarker<'a, T, Tag>,
+}
+
+impl<'a, T: Linked<Tag>, Tag> List<'a, T, Tag> {
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed),
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        item.links().list.get() == self.id
+    }
+
+    pub fn front(&self) -> Option<Pin<&'a T>> {
+        self.head.map(|head| unsafe { Self::pinned(head) })
+    }
+
+    pub fn back(&self) -> Option<Pin<&'a T>> {
+        self.tail.map(|tail| unsafe { Self::pinned(tail) })
+    }
+
+    pub fn push_front(&mut self, item: Pin<&'a T>) {
+        let next = self.head;
+        self.link(None, item, next);
+    }
+
+    pub fn push_back(&mut self, item: Pin<&'a T>) {
+        let prev = self.tail;
+        self.link(prev, item, None);
+    }
+
+    pub fn insert_before(&mut self, anchor: &T, item: Pin<&'a T>) {
+        assert!(self.contains(anchor), "anchor is not in this list");
+        let anchor = self.node(anchor);
+        let prev = unsafe { anchor.as_ref() }.links().prev.get();
+        self.link(prev, item, Some(anchor));
+    }
+
+    pub fn insert_after(&mut self, anchor: &T, item: Pin<&'a T>) {
+        assert!(self.contains(anchor), "anchor is not in this list");
+        let anchor = self.node(anchor);
+        let next = unsafe { anchor.as_ref() }.links().next.get();
+        self.link(Some(anchor), item, next);
+    }
+
+    pub fn pop_front(&mut self) -> Option<Pin<&'a T>> {
+        let head = self.front()?;
+        self.remove(&head);
+        Some(head)
+    }
+
+    pub fn pop_back(&mut self) -> Option<Pin<&'a T>> {
+        let tail = self.back()?;
+        self.remove(&tail);
+        Some(tail)
+    }
+
+    pub fn remove(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        let links = item.links();
+        let (prev, next) = (links.prev.get(), links.next.get());
+        match prev {
+            Some(prev) => unsafe { prev.as_ref() }.links().next.set(next),
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => unsafe { next.as_ref() }.links().prev.set(prev),
+            None => self.tail = prev,
+        }
+        links.unlink();
+        self.len -= 1;
+        true
+    }
+
+    pub fn move_to_front(&mut self, item: Pin<&'a T>) {
+        self.remove(&item);
+        self.push_front(item);
+    }
+
+    pub fn move_to_back(&mut self, item: Pin<&'a T>) {
+        self.remove(&item);
+        self.push_back(item);
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, 'a, T, Tag> {
+        Iter {
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    fn link(&mut self, prev: Option<NonNull<T>>, item: Pin<&'a T>, next: Option<NonNull<T>>) {
+        let links = item.links();
+        assert!(!links.is_linked(), "item is already linked into a list");
+        let node = NonNull::from(item.get_ref());
+        links.list.set(self.id);
+        links.prev.set(prev);
+        links.next.set(next);
+        match prev {
+            Some(prev) => unsafe { prev.as_ref() }.links().next.set(Some(node)),
+            None => self.head = Some(node),
+        }
+        match next {
+            Some(next) => unsafe { next.as_ref() }.links().prev.set(Some(node)),
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+    }
+
+    // the pointer the neighbours keep to an item of this list
+    fn node(&self, item: &T) -> NonNull<T> {
+        let found = match item.links().prev.get() {
+            Some(prev) => unsafe { prev.as_ref() }.links().next.get(),
+            None => self.head,
+        };
+        found.unwrap()
+    }
+
+    // every pointer in the list came from a `Pin<&'a T>`
+    unsafe fn pinned(node: NonNull<T>) -> Pin<&'a T> {
+        Pin::new_unchecked(&*node.as_ptr())
+    }
+}
+
+impl<'a, T: Linked<Tag>, Tag> Default for List<'a, T, Tag> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// leaves the items unlinked, so they can go into another list
+impl<'a, T: Linked<Tag>, Tag> Drop for List<'a, T, Tag> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'a, T: Linked<Tag>, Tag> Extend<Pin<&'a T>> for List<'a, T, Tag> {
+    fn extend<I: IntoIterator<Item = Pin<&'a T>>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+pub struct Iter<'l, 'a, T: Linked<Tag>, Tag> {
+    front: Option<NonNull<T>>,
+    back: Option<NonNull<T>>,
+    len: usize,
+    marker: PhantomData<&'l List<'a, T, Tag>>,
+}
+
+impl<'l, 'a, T: Linked<Tag>, Tag> Iterator for Iter<'l, 'a, T, Tag> {
+    type Item = Pin<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|front| {
+            let item = unsafe { List::<T, Tag>::pinned(front) };
+            self.front = item.links().next.get();
+            self.len -= 1;
+            item
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'l, 'a, T: Linked<Tag>, Tag> DoubleEndedIterator for Iter<'l, 'a, T, Tag> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|back| {
+            let item = unsafe { List::<T, Tag>::pinned(back) };
+            self.back = item.links().prev.get();
+            self.len -= 1;
+            item
+        })
+    }
+}
+
+impl<'l, 'a, T: Linked<Tag>, Tag> ExactSizeIterator for Iter<'l, 'a, T, Tag> {}
+
+impl<'l, 'a, T: Linked<Tag>, Tag> IntoIterator for &'l List<'a, T, Tag> {
+    type Item = Pin<&'a T>;
+    type IntoIter = Iter<'l, 'a, T, Tag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Item {
+        value: i32,
+        all: Links<Item>,
+        even: Links<Item>,
+    }
+
+    struct Even;
+
+    unsafe impl Linked for Item {
+        fn links(&self) -> &Links<Self> {
+            &self.all
+        }
+    }
+
+    unsafe impl Linked<Even> for Item {
+        fn links(&self) -> &Links<Self> {
+            &self.even
+        }
+    }
+
+    fn items(values: std::ops::Range<i32>) -> Vec<Pin<Box<Item>>> {
+        values
+            .map(|value| {
+                Box::pin(Item {
+                    value,
+                    all: Links::new(),
+                    even: Links::new(),
+                })
+            })
+            .collect()
+    }
+
+    fn values<'a, Tag>(list: &List<'a, Item, Tag>) -> Vec<i32>
+    where
+        Item: Linked<Tag>,
+    {
+        list.iter().map(|item| item.value).collect()
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut list: List<Item> = List::new();
+
+        assert!(list.is_empty());
+        assert!(list.front().is_none());
+        assert!(list.pop_back().is_none());
+        assert_eq!(list.iter().next().map(|item| item.value), None);
+    }
+
+    #[test]
+    fn push_and_pop_at_both_ends() {
+        let items = items(0..4);
+        let mut list: List<Item> = List::new();
+
+        list.push_back(items[1].as_ref());
+        list.push_back(items[2].as_ref());
+        list.push_front(items[0].as_ref());
+        list.push_back(items[3].as_ref());
+
+        assert_eq!(list.len(), 4);
+        assert_eq!(values(&list), vec![0, 1, 2, 3]);
+        assert_eq!(list.pop_front().map(|item| item.value), Some(0));
+        assert_eq!(list.pop_back().map(|item| item.value), Some(3));
+        assert_eq!(list.front().map(|item| item.value), Some(1));
+        assert_eq!(list.back().map(|item| item.value), Some(2));
+        assert!(!items[0].all.is_linked());
+    }
+
+    #[test]
+    fn insert_around_an_anchor() {
+        let items = items(0..4);
+        let mut list: List<Item> = List::new();
+        list.push_back(items[1].as_ref());
+
+        list.insert_before(&items[1], items[0].as_ref());
+        list.insert_after(&items[1], items[3].as_ref());
+        list.insert_after(&items[1], items[2].as_ref());
+
+        assert_eq!(values(&list), vec![0, 1, 2, 3]);
+        assert_eq!(
+            list.iter().rev().map(|item| item.value).collect::<Vec<_>>(),
+            vec![3, 2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn remove_from_the_middle() {
+        let items = items(0..5);
+        let mut list: List<Item> = List::new();
+        list.extend(items.iter().map(|item| item.as_ref()));
+
+        assert!(list.remove(&items[2]));
+        assert!(!list.remove(&items[2]));
+        assert!(list.remove(&items[0]));
+        assert!(list.remove(&items[4]));
+
+        assert_eq!(values(&list), vec![1, 3]);
+        assert!(!list.contains(&items[2]));
+        assert!(list.contains(&items[3]));
+    }
+
+    #[test]
+    fn move_to_either_end() {
+        let items = items(0..4);
+        let mut list: List<Item> = List::new();
+        list.extend(items.iter().map(|item| item.as_ref()));
+
+        list.move_to_front(items[2].as_ref());
+        list.move_to_back(items[0].as_ref());
+
+        assert_eq!(values(&list), vec![2, 1, 3, 0]);
+    }
+
+    #[test]
+    fn item_lives_in_several_lists() {
+        let items = items(0..6);
+        let mut all: List<Item> = List::new();
+        let mut even: List<Item, Even> = List::new();
+
+        for item in &items {
+            all.push_back(item.as_ref());
+            if item.value % 2 == 0 {
+                even.push_front(item.as_ref());
+            }
+        }
+        all.remove(&items[2]);
+
+        assert_eq!(values(&all), vec![0, 1, 3, 4, 5]);
+        assert_eq!(values(&even), vec![4, 2, 0]);
+    }
+
+    #[test]
+    fn item_can_move_between_lists() {
+        let items = items(0..2);
+        let mut first: List<Item> = List::new();
+        let mut second: List<Item> = List::new();
+        first.push_back(items[0].as_ref());
+
+        assert!(!second.remove(&items[0]));
+        first.remove(&items[0]);
+        second.push_back(items[0].as_ref());
+
+        assert!(first.is_empty());
+        assert!(second.contains(&items[0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "already linked")]
+    fn item_is_in_one_list_per_links() {
+        let items = items(0..1);
+        let mut first: List<Item> = List::new();
+        let mut second: List<Item> = List::new();
+
+        first.push_back(items[0].as_ref());
+        second.push_back(items[0].as_ref());
+    }
+
+    #[test]
+    fn dropped_list_unlinks_items() {
+        let items = items(0..3);
+        {
+            let mut list: List<Item> = List::new();
+            list.extend(items.iter().map(|item| item.as_ref()));
+        }
+
+        let mut list: List<Item> = List::new();
+        list.push_back(items[1].as_ref());
+
+        assert!(!items[0].all.is_linked());
+        assert_eq!(values(&list), vec![1]);
+    }
+}
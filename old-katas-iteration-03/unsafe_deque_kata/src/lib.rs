@@ -12,3 +12,8 @@ pub mod day_11;
 pub mod day_12;
 pub mod day_13;
 pub mod day_14;
+pub mod ring_deque;
+pub mod work_stealing;
+pub mod thread_pool;
+pub mod intrusive;
+pub mod cache;
@@ -0,0 +1,572 @@
+use std::mem::{self, MaybeUninit};
+use std::ops::{Index, IndexMut};
+use std::ptr;
+use std::slice;
+
+const MIN_CAPACITY: usize = 4;
+
+// items live in `buf[head..]` and wrap around to the start of `buf`;
+// slots outside of those `len` items are uninitialized
+pub struct RingDeque<T> {
+    buf: Box<[MaybeUninit<T>]>,
+    head: usize,
+    len: usize,
+}
+
+impl<T> RingDeque<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { buf: buffer(capacity), head: 0, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn push_front(&mut self, item: T) {
+        self.grow_if_full();
+        self.head = self.wrap(self.head + self.capacity() - 1);
+        self.buf[self.head] = MaybeUninit::new(item);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, item: T) {
+        self.grow_if_full();
+        let tail = self.wrap(self.head + self.len);
+        self.buf[tail] = MaybeUninit::new(item);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let item = unsafe { self.buf[self.head].assume_init_read() };
+        self.head = self.wrap(self.head + 1);
+        self.len -= 1;
+        Some(item)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        let tail = self.wrap(self.head + self.len);
+        Some(unsafe { self.buf[tail].assume_init_read() })
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|last| self.get(last))
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.len.checked_sub(1).and_then(move |last| self.get_mut(last))
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            Some(unsafe { self.buf[self.wrap(self.head + index)].assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            let slot = self.wrap(self.head + index);
+            Some(unsafe { self.buf[slot].assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.ranges();
+        let buf = self.buf.as_ptr() as *const T;
+        unsafe {
+            (
+                slice::from_raw_parts(buf.add(front.0), front.1),
+                slice::from_raw_parts(buf, back),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.ranges();
+        let buf = self.buf.as_mut_ptr() as *mut T;
+        unsafe {
+            (
+                slice::from_raw_parts_mut(buf.add(front.0), front.1),
+                slice::from_raw_parts_mut(buf, back),
+            )
+        }
+    }
+
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head + self.len > self.capacity() {
+            // the free slots sit between the back and the front part, rotating
+            // the whole buffer moves them after the items
+            self.buf.rotate_left(self.head);
+            self.head = 0;
+        }
+        self.as_mut_slices().0
+    }
+
+    // moves the first `n` items to the back
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "cannot rotate {} items in a deque of length {}", n, self.len);
+        if n > self.len / 2 {
+            return self.rotate_right(self.len - n);
+        }
+        if self.len == self.capacity() {
+            self.head = self.wrap(self.head + n);
+            return;
+        }
+        for _ in 0..n {
+            let item = self.pop_front().unwrap();
+            self.push_back(item);
+        }
+    }
+
+    // moves the last `n` items to the front
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "cannot rotate {} items in a deque of length {}", n, self.len);
+        if n > self.len / 2 {
+            return self.rotate_left(self.len - n);
+        }
+        if self.len == self.capacity() {
+            self.head = self.wrap(self.head + self.capacity() - n);
+            return;
+        }
+        for _ in 0..n {
+            let item = self.pop_back().unwrap();
+            self.push_front(item);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> RingDequeIterRef<'_, T> {
+        self.into_iter()
+    }
+
+    pub fn iter_mut(&mut self) -> RingDequeIterRefMut<'_, T> {
+        self.into_iter()
+    }
+
+    fn wrap(&self, index: usize) -> usize {
+        if index >= self.capacity() {
+            index - self.capacity()
+        } else {
+            index
+        }
+    }
+
+    // start and length of the front part, length of the wrapped back part
+    fn ranges(&self) -> ((usize, usize), usize) {
+        let to_end = self.capacity() - self.head;
+        if self.len <= to_end {
+            ((self.head, self.len), 0)
+        } else {
+            ((self.head, to_end), self.len - to_end)
+        }
+    }
+
+    fn grow_if_full(&mut self) {
+        if self.len < self.capacity() {
+            return;
+        }
+        let mut buf = buffer((self.capacity() * 2).max(MIN_CAPACITY));
+        let (front, back) = self.ranges();
+        unsafe {
+            let old = self.buf.as_ptr();
+            let new = buf.as_mut_ptr();
+            ptr::copy_nonoverlapping(old.add(front.0), new, front.1);
+            ptr::copy_nonoverlapping(old, new.add(front.1), back);
+        }
+        // the old buffer only holds bitwise copies now and drops nothing
+        self.buf = buf;
+        self.head = 0;
+    }
+}
+
+fn buffer<T>(capacity: usize) -> Box<[MaybeUninit<T>]> {
+    (0..capacity).map(|_| MaybeUninit::uninit()).collect()
+}
+
+impl<T> Default for RingDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for RingDeque<T> {
+    fn drop(&mut self) {
+        // drops the back part even if dropping an item of the front part panics
+        struct Guard<'d, T>(&'d mut [T]);
+
+        impl<'d, T> Drop for Guard<'d, T> {
+            fn drop(&mut self) {
+                unsafe { ptr::drop_in_place(self.0) }
+            }
+        }
+
+        let (front, back) = self.as_mut_slices();
+        let _back = Guard(back);
+        unsafe { ptr::drop_in_place(front) }
+    }
+}
+
+impl<T> Index<usize> for RingDeque<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index).unwrap_or_else(|| panic!("index {} out of bounds of deque of length {}", index, len))
+    }
+}
+
+impl<T> IndexMut<usize> for RingDeque<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index).unwrap_or_else(|| panic!("index {} out of bounds of deque of length {}", index, len))
+    }
+}
+
+impl<T> Extend<T> for RingDeque<T> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, items: I) {
+        for item in items {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> IntoIterator for RingDeque<T> {
+    type Item = T;
+    type IntoIter = RingDequeIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        RingDequeIter { deque: self }
+    }
+}
+
+pub struct RingDequeIter<T> {
+    deque: RingDeque<T>
+}
+
+impl<T> Iterator for RingDequeIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.deque.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.deque.len, Some(self.deque.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for RingDequeIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.deque.pop_back()
+    }
+}
+
+impl<T> AsRef<Self> for RingDeque<T> {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<'d, T> IntoIterator for &'d RingDeque<T> {
+    type Item = &'d T;
+    type IntoIter = RingDequeIterRef<'d, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let (front, back) = self.as_slices();
+        RingDequeIterRef { front: front.iter(), back: back.iter() }
+    }
+}
+
+pub struct RingDequeIterRef<'d, T> {
+    front: slice::Iter<'d, T>,
+    back: slice::Iter<'d, T>,
+}
+
+impl<'d, T> Iterator for RingDequeIterRef<'d, T> {
+    type Item = &'d T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.front.next() {
+            Some(item) => Some(item),
+            None => {
+                mem::swap(&mut self.front, &mut self.back);
+                self.front.next()
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'d, T> DoubleEndedIterator for RingDequeIterRef<'d, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.back.next_back() {
+            Some(item) => Some(item),
+            None => self.front.next_back()
+        }
+    }
+}
+
+impl<T> AsMut<Self> for RingDeque<T> {
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+impl<'d, T> IntoIterator for &'d mut RingDeque<T> {
+    type Item = &'d mut T;
+    type IntoIter = RingDequeIterRefMut<'d, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let (front, back) = self.as_mut_slices();
+        RingDequeIterRefMut { front: front.iter_mut(), back: back.iter_mut() }
+    }
+}
+
+pub struct RingDequeIterRefMut<'d, T> {
+    front: slice::IterMut<'d, T>,
+    back: slice::IterMut<'d, T>,
+}
+
+impl<'d, T> Iterator for RingDequeIterRefMut<'d, T> {
+    type Item = &'d mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.front.next() {
+            Some(item) => Some(item),
+            None => {
+                mem::swap(&mut self.front, &mut self.back);
+                self.front.next()
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'d, T> DoubleEndedIterator for RingDequeIterRefMut<'d, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.back.next_back() {
+            Some(item) => Some(item),
+            None => self.front.next_back()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deque(items: &[i32]) -> RingDeque<i32> {
+        let mut deque = RingDeque::new();
+        deque.extend(items.iter().cloned());
+        deque
+    }
+
+    // items 1..=len with the front part at the end of a full buffer
+    fn wrapped(len: i32) -> RingDeque<i32> {
+        let mut deque = RingDeque::with_capacity(len as usize);
+        for item in (1..=len / 2).rev() {
+            deque.push_front(item);
+        }
+        deque.extend(len / 2 + 1..=len);
+        deque
+    }
+
+    fn items(deque: RingDeque<i32>) -> Vec<i32> {
+        deque.into_iter().collect()
+    }
+
+    #[test]
+    fn pop_front_from_empty_deque() {
+        let mut deque: RingDeque<i32> = RingDeque::default();
+
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+        assert_eq!(deque.capacity(), 0);
+    }
+
+    #[test]
+    fn push_and_pop_at_both_ends() {
+        let mut deque = RingDeque::new();
+
+        deque.push_front(2);
+        deque.push_back(3);
+        deque.push_front(1);
+
+        assert_eq!(deque.len(), 3);
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_front(), Some(2));
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn grows_while_wrapped() {
+        let mut deque = wrapped(4);
+
+        assert_eq!(deque.capacity(), 4);
+        deque.push_back(5);
+        deque.push_front(0);
+
+        assert_eq!(deque.capacity(), 8);
+        assert_eq!(items(deque), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn peek() {
+        let mut deque = deque(&[1, 2, 3]);
+
+        *deque.peek_front_mut().unwrap() *= 10;
+        *deque.peek_back_mut().unwrap() *= 10;
+
+        assert_eq!(deque.peek_front(), Some(&10));
+        assert_eq!(deque.peek_back(), Some(&30));
+        assert_eq!(RingDeque::<i32>::new().peek_back(), None);
+    }
+
+    #[test]
+    fn indexing() {
+        let mut deque = wrapped(6);
+
+        deque[4] *= 10;
+
+        assert_eq!(deque[0], 1);
+        assert_eq!(deque[4], 50);
+        assert_eq!(deque.get(6), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn indexing_out_of_bounds() {
+        let _ = deque(&[1])[1];
+    }
+
+    #[test]
+    fn slices_of_wrapped_deque() {
+        let mut deque = wrapped(6);
+
+        assert_eq!(deque.as_slices(), (&[1, 2, 3][..], &[4, 5, 6][..]));
+        assert_eq!(deque.make_contiguous(), &mut [1, 2, 3, 4, 5, 6]);
+        assert_eq!(deque.as_slices(), (&[1, 2, 3, 4, 5, 6][..], &[][..]));
+    }
+
+    #[test]
+    fn make_contiguous_with_free_slots() {
+        let mut deque = wrapped(8);
+        deque.pop_back();
+        deque.pop_front();
+
+        deque.make_contiguous().reverse();
+        deque.push_back(0);
+
+        assert_eq!(items(deque), vec![7, 6, 5, 4, 3, 2, 0]);
+    }
+
+    #[test]
+    fn rotate() {
+        let mut deque = deque(&[1, 2, 3, 4, 5]);
+
+        deque.rotate_left(2);
+        assert_eq!(deque.iter().cloned().collect::<Vec<_>>(), vec![3, 4, 5, 1, 2]);
+        deque.rotate_left(4);
+        assert_eq!(deque.iter().cloned().collect::<Vec<_>>(), vec![2, 3, 4, 5, 1]);
+        deque.rotate_right(1);
+        deque.rotate_right(5);
+
+        assert_eq!(items(deque), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn rotate_full_deque() {
+        let mut deque = wrapped(4);
+
+        deque.rotate_left(1);
+        deque.rotate_right(2);
+
+        assert_eq!(deque.capacity(), 4);
+        assert_eq!(items(deque), vec![4, 1, 2, 3]);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut deque = wrapped(6);
+
+        for item in deque.iter_mut() {
+            *item *= 2;
+        }
+        let mut iter = deque.iter();
+
+        assert_eq!(iter.size_hint(), (6, Some(6)));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), Some(&12));
+        assert_eq!(iter.rev().collect::<Vec<_>>(), vec![&10, &8, &6, &4]);
+        assert_eq!(deque.as_mut().into_iter().next_back(), Some(&mut 12));
+        assert_eq!(deque.into_iter().rev().collect::<Vec<_>>(), vec![12, 10, 8, 6, 4, 2]);
+    }
+
+    #[test]
+    fn zero_sized_items() {
+        let mut deque = RingDeque::new();
+
+        deque.extend(vec![(); 10]);
+        deque.push_front(());
+        deque.rotate_left(3);
+
+        assert_eq!(deque.len(), 11);
+        assert_eq!(deque.make_contiguous().len(), 11);
+        assert_eq!(deque.into_iter().count(), 11);
+    }
+
+    #[test]
+    fn drop_frees_remaining_items() {
+        let item = std::rc::Rc::new(());
+        let mut deque = RingDeque::with_capacity(2);
+
+        deque.push_back(item.clone());
+        deque.push_front(item.clone());
+        deque.push_back(item.clone());
+        drop(deque);
+
+        assert_eq!(std::rc::Rc::strong_count(&item), 1);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::work_stealing::{Steal, Stealer, Worker};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+// how many jobs a worker moves from the shared queue into its own deque at once,
+// the rest of the pool steals them from there
+const BATCH: usize = 16;
+
+struct Shared {
+    injector: Mutex<VecDeque<Job>>,
+    available: Condvar,
+    shutdown: AtomicBool,
+    // the first job panic, handed back to the owner once the pool is dropped
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+pub struct ThreadPool {
+    shared: Arc<Shared>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "thread pool needs at least one thread");
+        let shared = Arc::new(Shared {
+            injector: Mutex::new(VecDeque::new()),
+            available: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+            panic: Mutex::new(None),
+        });
+        let workers: Vec<Worker<Job>> = (0..size).map(|_| Worker::new()).collect();
+        let stealers: Vec<Stealer<Job>> = workers.iter().map(Worker::stealer).collect();
+        let threads = workers
+            .into_iter()
+            .enumerate()
+            .map(|(index, worker)| {
+                let shared = shared.clone();
+                let others: Vec<Stealer<Job>> = stealers
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != index)
+                    .map(|(_, stealer)| stealer.clone())
+                    .collect();
+                thread::spawn(move || run(&worker, &others, &shared))
+            })
+            .collect();
+        Self { shared, threads }
+    }
+
+    pub fn execute<F: FnOnce() + Send + 'static>(&self, job: F) {
+        self.shared.injector.lock().unwrap().push_back(Box::new(job));
+        self.shared.available.notify_one();
+    }
+}
+
+// waits for every submitted job to finish, then resumes the first job panic if any
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        self.shared.available.notify_all();
+        for thread in self.threads.drain(..) {
+            thread.join().expect("jobs run under catch_unwind, workers don't panic");
+        }
+        let panic = self.shared.panic.lock().unwrap().take();
+        if let Some(panic) = panic {
+            if !thread::panicking() {
+                panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+fn run(worker: &Worker<Job>, others: &[Stealer<Job>], shared: &Shared) {
+    loop {
+        match find_job(worker, others, shared) {
+            Some(job) => {
+                if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(job)) {
+                    shared.panic.lock().unwrap().get_or_insert(panic);
+                }
+            }
+            None => {
+                let injector = shared.injector.lock().unwrap();
+                if injector.is_empty() {
+                    if shared.shutdown.load(Ordering::SeqCst) && others.iter().all(Stealer::is_empty) {
+                        return;
+                    }
+                    // jobs pushed into other workers' deques don't notify, so don't sleep for long
+                    let _ = shared.available.wait_timeout(injector, Duration::from_millis(1)).unwrap();
+                }
+            }
+        }
+    }
+}
+
+fn find_job(worker: &Worker<Job>, others: &[Stealer<Job>], shared: &Shared) -> Option<Job> {
+    if let Some(job) = worker.pop() {
+        return Some(job);
+    }
+    {
+        let mut injector = shared.injector.lock().unwrap();
+        if let Some(job) = injector.pop_front() {
+            for _ in 0..BATCH.min(injector.len() / 2) {
+                worker.push(injector.pop_front().unwrap());
+            }
+            return Some(job);
+        }
+    }
+    loop {
+        let mut retry = false;
+        for stealer in others {
+            match stealer.steal() {
+                Steal::Success(job) => return Some(job),
+                Steal::Retry => retry = true,
+                Steal::Empty => {}
+            }
+        }
+        if !retry {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Barrier;
+
+    const JOBS: usize = if cfg!(miri) { 100 } else { 20_000 };
+
+    #[test]
+    fn runs_every_job_exactly_once() {
+        let runs: Arc<Vec<AtomicUsize>> = Arc::new((0..JOBS).map(|_| AtomicUsize::new(0)).collect());
+        let pool = ThreadPool::new(4);
+
+        for job in 0..JOBS {
+            let runs = runs.clone();
+            pool.execute(move || {
+                runs[job].fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        drop(pool);
+
+        assert!(runs.iter().all(|count| count.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn jobs_are_spread_over_threads() {
+        let threads = Arc::new(Mutex::new(std::collections::HashSet::new()));
+        // no job gets past the barrier until every thread is running one
+        let barrier = Arc::new(Barrier::new(4));
+        let pool = ThreadPool::new(4);
+
+        for _ in 0..4 {
+            let threads = threads.clone();
+            let barrier = barrier.clone();
+            pool.execute(move || {
+                threads.lock().unwrap().insert(thread::current().id());
+                barrier.wait();
+            });
+        }
+        drop(pool);
+
+        assert_eq!(threads.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn panicking_job_does_not_stop_the_pool() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let pool = ThreadPool::new(2);
+
+        for job in 0..JOBS {
+            let runs = runs.clone();
+            pool.execute(move || {
+                if job % 10 == 0 {
+                    panic!("job {} failed", job);
+                }
+                runs.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        let panic = panic::catch_unwind(AssertUnwindSafe(|| drop(pool))).unwrap_err();
+
+        assert_eq!(runs.load(Ordering::Relaxed), JOBS - JOBS / 10);
+        assert!(panic.downcast_ref::<String>().unwrap().ends_with("failed"));
+    }
+
+    #[test]
+    fn single_thread_pool() {
+        let sum = Arc::new(AtomicUsize::new(0));
+        let pool = ThreadPool::new(1);
+
+        for job in 1..=100 {
+            let sum = sum.clone();
+            pool.execute(move || {
+                sum.fetch_add(job, Ordering::Relaxed);
+            });
+        }
+        drop(pool);
+
+        assert_eq!(sum.load(Ordering::Relaxed), 5050);
+    }
+}
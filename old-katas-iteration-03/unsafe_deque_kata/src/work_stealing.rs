@@ -0,0 +1,341 @@
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicIsize, AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
+
+const MIN_CAPACITY: usize = 16;
+
+#[derive(Debug, PartialEq)]
+pub enum Steal<T> {
+    Success(T),
+    Empty,
+    Retry,
+}
+
+// slots hold boxed items behind atomic pointers, so a stealer that races with
+// the owner reads a stale pointer at worst and throws it away when it loses the CAS
+struct Buffer<T> {
+    slots: Box<[AtomicPtr<T>]>,
+}
+
+impl<T> Buffer<T> {
+    fn new(capacity: usize) -> Self {
+        Self { slots: (0..capacity).map(|_| AtomicPtr::new(ptr::null_mut())).collect() }
+    }
+
+    fn capacity(&self) -> isize {
+        self.slots.len() as isize
+    }
+
+    fn slot(&self, index: isize) -> &AtomicPtr<T> {
+        &self.slots[index as usize & (self.slots.len() - 1)]
+    }
+
+    fn get(&self, index: isize) -> *mut T {
+        self.slot(index).load(Ordering::Relaxed)
+    }
+
+    fn put(&self, index: isize, item: *mut T) {
+        self.slot(index).store(item, Ordering::Relaxed)
+    }
+}
+
+struct Inner<T> {
+    top: AtomicIsize,
+    bottom: AtomicIsize,
+    buffer: AtomicPtr<Buffer<T>>,
+    // stealers may still read from a buffer the owner has outgrown, so it stays
+    // a raw pointer (never a unique `Box`) until the last handle is gone
+    retired: Mutex<Vec<*mut Buffer<T>>>,
+    marker: PhantomData<*mut T>,
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let top = *self.top.get_mut();
+        let bottom = *self.bottom.get_mut();
+        let buffer = unsafe { Box::from_raw(*self.buffer.get_mut()) };
+        for index in top..bottom {
+            drop(unsafe { Box::from_raw(buffer.get(index)) });
+        }
+        for retired in self.retired.get_mut().unwrap().drain(..) {
+            drop(unsafe { Box::from_raw(retired) });
+        }
+    }
+}
+
+// the owner pushes and pops at the bottom, stealers take from the top
+pub struct Worker<T> {
+    inner: Arc<Inner<T>>,
+}
+
+unsafe impl<T: Send> Send for Worker<T> {}
+
+pub struct Stealer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+unsafe impl<T: Send> Send for Stealer<T> {}
+unsafe impl<T: Send> Sync for Stealer<T> {}
+
+impl<T> Worker<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(MIN_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let buffer = Box::new(Buffer::new(capacity.max(1).next_power_of_two()));
+        Self {
+            inner: Arc::new(Inner {
+                top: AtomicIsize::new(0),
+                bottom: AtomicIsize::new(0),
+                buffer: AtomicPtr::new(Box::into_raw(buffer)),
+                retired: Mutex::new(vec![]),
+                marker: PhantomData,
+            }),
+        }
+    }
+
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer { inner: self.inner.clone() }
+    }
+
+    pub fn len(&self) -> usize {
+        let bottom = self.inner.bottom.load(Ordering::Relaxed);
+        let top = self.inner.top.load(Ordering::Relaxed);
+        (bottom - top).max(0) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&self, item: T) {
+        let bottom = self.inner.bottom.load(Ordering::Relaxed);
+        let top = self.inner.top.load(Ordering::Acquire);
+        let mut buffer = self.buffer();
+        if bottom - top >= buffer.capacity() {
+            buffer = self.grow(buffer, top, bottom);
+        }
+        buffer.put(bottom, Box::into_raw(Box::new(item)));
+        fence(Ordering::Release);
+        self.inner.bottom.store(bottom + 1, Ordering::Relaxed);
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let bottom = self.inner.bottom.load(Ordering::Relaxed) - 1;
+        let buffer = self.buffer();
+        self.inner.bottom.store(bottom, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let top = self.inner.top.load(Ordering::Relaxed);
+        if top > bottom {
+            self.inner.bottom.store(bottom + 1, Ordering::Relaxed);
+            return None;
+        }
+        let item = buffer.get(bottom);
+        if top == bottom {
+            // the last item, a stealer may be after it too
+            let won = self.inner.top
+                .compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok();
+            self.inner.bottom.store(bottom + 1, Ordering::Relaxed);
+            if !won {
+                return None;
+            }
+        }
+        Some(*unsafe { Box::from_raw(item) })
+    }
+
+    fn buffer(&self) -> &Buffer<T> {
+        unsafe { &*self.inner.buffer.load(Ordering::Relaxed) }
+    }
+
+    fn grow(&self, old: &Buffer<T>, top: isize, bottom: isize) -> &Buffer<T> {
+        let new = Buffer::new(old.slots.len() * 2);
+        for index in top..bottom {
+            new.put(index, old.get(index));
+        }
+        let new = Box::into_raw(Box::new(new));
+        let old = self.inner.buffer.swap(new, Ordering::Release);
+        self.inner.retired.lock().unwrap().push(old);
+        unsafe { &*new }
+    }
+}
+
+impl<T> Default for Worker<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Stealer<T> {
+    pub fn steal(&self) -> Steal<T> {
+        let top = self.inner.top.load(Ordering::Acquire);
+        fence(Ordering::SeqCst);
+        let bottom = self.inner.bottom.load(Ordering::Acquire);
+        if top >= bottom {
+            return Steal::Empty;
+        }
+        let buffer = unsafe { &*self.inner.buffer.load(Ordering::Acquire) };
+        let item = buffer.get(top);
+        match self.inner.top.compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed) {
+            Ok(_) => Steal::Success(*unsafe { Box::from_raw(item) }),
+            Err(_) => Steal::Retry,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let top = self.inner.top.load(Ordering::Acquire);
+        let bottom = self.inner.bottom.load(Ordering::Acquire);
+        top >= bottom
+    }
+}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Stealer { inner: self.inner.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    const ITEMS: usize = if cfg!(miri) { 200 } else { 100_000 };
+
+    fn steal_all<T>(stealer: &Stealer<T>) -> Vec<T> {
+        let mut stolen = vec![];
+        loop {
+            match stealer.steal() {
+                Steal::Success(item) => stolen.push(item),
+                Steal::Retry => {}
+                Steal::Empty => return stolen,
+            }
+        }
+    }
+
+    #[test]
+    fn pop_from_empty_deque() {
+        let worker: Worker<i32> = Worker::new();
+
+        assert_eq!(worker.pop(), None);
+        assert_eq!(worker.stealer().steal(), Steal::Empty);
+        assert!(worker.is_empty());
+    }
+
+    #[test]
+    fn owner_pops_last_pushed_item() {
+        let worker = Worker::new();
+
+        worker.push(1);
+        worker.push(2);
+        worker.push(3);
+
+        assert_eq!(worker.len(), 3);
+        assert_eq!(worker.pop(), Some(3));
+        assert_eq!(worker.pop(), Some(2));
+        assert_eq!(worker.pop(), Some(1));
+        assert_eq!(worker.pop(), None);
+    }
+
+    #[test]
+    fn stealer_takes_first_pushed_item() {
+        let worker = Worker::new();
+        let stealer = worker.stealer();
+
+        worker.push(1);
+        worker.push(2);
+        worker.push(3);
+
+        assert_eq!(stealer.steal(), Steal::Success(1));
+        assert_eq!(worker.pop(), Some(3));
+        assert_eq!(stealer.clone().steal(), Steal::Success(2));
+        assert_eq!(stealer.steal(), Steal::Empty);
+        assert!(stealer.is_empty());
+    }
+
+    #[test]
+    fn grows_past_initial_capacity() {
+        let worker = Worker::with_capacity(2);
+        let stealer = worker.stealer();
+
+        for item in 0..100 {
+            worker.push(item);
+        }
+        assert_eq!(stealer.steal(), Steal::Success(0));
+        for item in 100..200 {
+            worker.push(item);
+        }
+
+        let mut items = steal_all(&stealer);
+        items.insert(0, 0);
+        assert_eq!(items, (0..200).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn drop_frees_remaining_items() {
+        let item = Arc::new(());
+        let worker = Worker::with_capacity(1);
+        let stealer = worker.stealer();
+
+        for _ in 0..10 {
+            worker.push(item.clone());
+        }
+        drop(worker.pop());
+        drop(stealer.steal());
+        drop(worker);
+        assert_eq!(Arc::strong_count(&item), 9);
+        drop(stealer);
+
+        assert_eq!(Arc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn concurrent_pops_and_steals_take_every_item_once() {
+        let worker = Worker::with_capacity(4);
+        let seen: Arc<Vec<AtomicUsize>> = Arc::new((0..ITEMS).map(|_| AtomicUsize::new(0)).collect());
+        let stealers: Vec<_> = (0..3)
+            .map(|_| {
+                let stealer = worker.stealer();
+                let seen = seen.clone();
+                thread::spawn(move || {
+                    let mut taken = 0;
+                    loop {
+                        match stealer.steal() {
+                            Steal::Success(ITEMS) => return taken,
+                            Steal::Success(item) => {
+                                seen[item].fetch_add(1, Ordering::Relaxed);
+                                taken += 1;
+                            }
+                            Steal::Empty | Steal::Retry => thread::yield_now(),
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let mut popped = 0;
+        for item in 0..ITEMS {
+            worker.push(item);
+            if item % 3 == 0 {
+                if let Some(item) = worker.pop() {
+                    seen[item].fetch_add(1, Ordering::Relaxed);
+                    popped += 1;
+                }
+            }
+        }
+        while let Some(item) = worker.pop() {
+            seen[item].fetch_add(1, Ordering::Relaxed);
+            popped += 1;
+        }
+        for _ in 0..stealers.len() {
+            worker.push(ITEMS);
+        }
+        let stolen: usize = stealers.into_iter().map(|stealer| stealer.join().unwrap()).sum();
+
+        assert_eq!(popped + stolen, ITEMS);
+        assert!(seen.iter().all(|count| count.load(Ordering::Relaxed) == 1));
+    }
+}
@@ -0,0 +1,176 @@
+// an LRU cache over a fixed set of preallocated slots: a slot sits either in the
+// `free` list or in the `recency` list through the same `Links`, the map points
+// straight at its slot, so `get` and `put` move a slot without allocating
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::pin::Pin;
+use unsafe_deque_kata::intrusive::{Linked, Links, List};
+
+struct Slot<K, V> {
+    entry: RefCell<Option<(K, V)>>,
+    links: Links<Slot<K, V>>,
+}
+
+unsafe impl<K, V> Linked for Slot<K, V> {
+    fn links(&self) -> &Links<Self> {
+        &self.links
+    }
+}
+
+fn slots<K, V>(capacity: usize) -> Vec<Pin<Box<Slot<K, V>>>> {
+    (0..capacity)
+        .map(|_| {
+            Box::pin(Slot {
+                entry: RefCell::new(None),
+                links: Links::new(),
+            })
+        })
+        .collect()
+}
+
+struct Lru<'a, K, V> {
+    map: HashMap<K, Pin<&'a Slot<K, V>>>,
+    recency: List<'a, Slot<K, V>>,
+    free: List<'a, Slot<K, V>>,
+}
+
+impl<'a, K: Hash + Eq + Clone, V: Clone> Lru<'a, K, V> {
+    fn new(slots: &'a [Pin<Box<Slot<K, V>>>]) -> Self {
+        let mut free = List::new();
+        free.extend(slots.iter().map(|slot| slot.as_ref()));
+        Lru {
+            map: HashMap::new(),
+            recency: List::new(),
+            free,
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let slot = *self.map.get(key)?;
+        self.recency.move_to_front(slot);
+        let entry = slot.entry.borrow();
+        entry.as_ref().map(|(_, value)| value.clone())
+    }
+
+    // returns the entry it had to evict to make room
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(&slot) = self.map.get(&key) {
+            *slot.entry.borrow_mut() = Some((key, value));
+            self.recency.move_to_front(slot);
+            return None;
+        }
+        let (slot, evicted) = match self.free.pop_front() {
+            Some(slot) => (slot, None),
+            None => {
+                let slot = self.recency.pop_back().expect("cache has no slots");
+                let evicted = slot.entry.borrow_mut().take();
+                if let Some((key, _)) = &evicted {
+                    self.map.remove(key);
+                }
+                (slot, evicted)
+            }
+        };
+        *slot.entry.borrow_mut() = Some((key.clone(), value));
+        self.map.insert(key, slot);
+        self.recency.push_front(slot);
+        evicted
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.map.remove(key)?;
+        self.recency.remove(&slot);
+        self.free.push_back(slot);
+        let entry = slot.entry.borrow_mut().take();
+        entry.map(|(_, value)| value)
+    }
+
+    fn keys(&self) -> Vec<K> {
+        self.recency
+            .iter()
+            .map(|slot| slot.entry.borrow().as_ref().unwrap().0.clone())
+            .collect()
+    }
+}
+
+#[test]
+fn evicts_least_recently_used() {
+    let slots = slots(3);
+    let mut lru = Lru::new(&slots);
+
+    assert_eq!(lru.put("a", 1), None);
+    assert_eq!(lru.put("b", 2), None);
+    assert_eq!(lru.put("c", 3), None);
+    assert_eq!(lru.get(&"a"), Some(1));
+
+    assert_eq!(lru.put("d", 4), Some(("b", 2)));
+    assert_eq!(lru.get(&"b"), None);
+    assert_eq!(lru.keys(), vec!["d", "a", "c"]);
+}
+
+#[test]
+fn put_refreshes_existing_key() {
+    let slots = slots(2);
+    let mut lru = Lru::new(&slots);
+
+    lru.put(1, "one");
+    lru.put(2, "two");
+    assert_eq!(lru.put(1, "uno"), None);
+
+    assert_eq!(lru.put(3, "three"), Some((2, "two")));
+    assert_eq!(lru.get(&1), Some("uno"));
+    assert_eq!(lru.keys(), vec![1, 3]);
+}
+
+#[test]
+fn removed_slot_is_reused() {
+    let slots = slots(2);
+    let mut lru = Lru::new(&slots);
+    lru.put(1, 10);
+    lru.put(2, 20);
+
+    assert_eq!(lru.remove(&1), Some(10));
+    assert_eq!(lru.remove(&1), None);
+
+    assert_eq!(lru.put(3, 30), None);
+    assert_eq!(lru.put(4, 40), Some((2, 20)));
+    assert_eq!(lru.keys(), vec![4, 3]);
+}
+
+#[test]
+fn matches_a_naive_cache() {
+    let slots = slots(8);
+    let mut lru = Lru::new(&slots);
+    let mut naive: Vec<(u32, u32)> = vec![];
+    let mut seed = 7u32;
+
+    for step in 0..if cfg!(miri) { 200 } else { 10_000 } {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        let key = (seed >> 16) % 16;
+        if seed.is_multiple_of(3) {
+            let expected = naive
+                .iter()
+                .position(|&(k, _)| k == key)
+                .map(|at| naive.remove(at));
+            if let Some(entry) = expected {
+                naive.insert(0, entry);
+            }
+            assert_eq!(lru.get(&key), expected.map(|(_, value)| value));
+        } else {
+            let expected = match naive.iter().position(|&(k, _)| k == key) {
+                Some(at) => {
+                    naive.remove(at);
+                    None
+                }
+                None if naive.len() == 8 => naive.pop(),
+                None => None,
+            };
+            naive.insert(0, (key, step));
+            assert_eq!(lru.put(key, step), expected);
+        }
+        assert_eq!(
+            lru.keys(),
+            naive.iter().map(|&(k, _)| k).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,336 @@
+// meant to be run under Miri with both aliasing models:
+//   cargo +nightly miri test -p unsafe_deque_kata --test soundness
+//   MIRIFLAGS=-Zmiri-tree-borrows cargo +nightly miri test -p unsafe_deque_kata --test soundness
+use std::cell::Cell;
+use std::hash::{Hash, Hasher};
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use unsafe_deque_kata::cache::{LfuCache, LruCache};
+use unsafe_deque_kata::day_14::UnsafeDeque;
+
+#[derive(Debug)]
+struct Tracked {
+    id: i32,
+    drops: Rc<Cell<usize>>,
+    panic_on_drop: bool,
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+        if self.panic_on_drop {
+            panic!("dropping {}", self.id);
+        }
+    }
+}
+
+struct Items {
+    drops: Rc<Cell<usize>>,
+}
+
+impl Items {
+    fn new() -> Self {
+        Items { drops: Rc::new(Cell::new(0)) }
+    }
+
+    fn item(&self, id: i32) -> Tracked {
+        Tracked { id, drops: self.drops.clone(), panic_on_drop: false }
+    }
+
+    fn panicking(&self, id: i32) -> Tracked {
+        Tracked { id, drops: self.drops.clone(), panic_on_drop: true }
+    }
+
+    fn deque(&self, ids: &[i32]) -> UnsafeDeque<Tracked> {
+        let mut deque = UnsafeDeque::default();
+        deque.extend(ids.iter().map(|&id| self.item(id)));
+        deque
+    }
+
+    fn drops(&self) -> usize {
+        self.drops.get()
+    }
+}
+
+fn ids(deque: &UnsafeDeque<Tracked>) -> Vec<i32> {
+    deque.into_iter().map(|item| item.id).collect()
+}
+
+#[test]
+fn push_pop_and_peek_at_both_ends() {
+    let items = Items::new();
+    let mut deque = UnsafeDeque::default();
+
+    deque.push_front(items.item(2));
+    deque.push_back(items.item(3));
+    deque.push_front(items.item(1));
+    if let Some(item) = deque.peek_front_mut() {
+        item.id *= 10;
+    }
+    if let Some(item) = deque.peek_back_mut() {
+        item.id *= 10;
+    }
+
+    assert_eq!(deque.peek_front().map(|item| item.id), Some(10));
+    assert_eq!(deque.peek_back().map(|item| item.id), Some(30));
+    assert_eq!(deque.pop_back().map(|item| item.id), Some(30));
+    assert_eq!(deque.pop_front().map(|item| item.id), Some(10));
+    assert_eq!(items.drops(), 2);
+    assert_eq!(deque.len(), 1);
+    drop(deque);
+    assert_eq!(items.drops(), 3);
+}
+
+#[test]
+fn iterators_interleaved_with_peeks() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1, 2, 3]);
+
+    for item in &mut deque {
+        item.id += 1;
+    }
+    let first = deque.peek_front().map(|item| item.id);
+    let sum: i32 = (&deque).into_iter().map(|item| item.id).sum();
+    for item in deque.as_mut() {
+        item.id -= 1;
+    }
+
+    assert_eq!(first, Some(2));
+    assert_eq!(sum, 9);
+    assert_eq!(ids(&deque), vec![1, 2, 3]);
+}
+
+#[test]
+fn partially_consumed_iterator_drops_the_rest() {
+    let items = Items::new();
+    let mut iter = items.deque(&[1, 2, 3, 4]).into_iter();
+
+    assert_eq!(iter.next().map(|item| item.id), Some(1));
+    assert_eq!(iter.next_back().map(|item| item.id), Some(4));
+    drop(iter);
+
+    assert_eq!(items.drops(), 4);
+}
+
+#[test]
+fn cursor_edits() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1, 2, 3]);
+    let mut cursor = deque.cursor_front_mut();
+
+    cursor.move_next();
+    drop(cursor.remove_current());
+    cursor.insert_before(items.item(4));
+    cursor.insert_after(items.item(5));
+    cursor.move_prev();
+    cursor.move_prev();
+    cursor.move_prev();
+    cursor.insert_after(items.item(0));
+    let after = cursor.split_after();
+    cursor.splice_after(items.deque(&[6, 7]));
+    cursor.move_next();
+    cursor.move_next();
+    let before = cursor.split_before();
+
+    assert_eq!(ids(&after), vec![0, 1, 4, 3, 5]);
+    assert_eq!(ids(&before), vec![6]);
+    assert_eq!(ids(&deque), vec![7]);
+    assert_eq!(items.drops(), 1);
+    drop((after, before, deque));
+    assert_eq!(items.drops(), 8);
+}
+
+#[test]
+fn append_split_off_retain_and_clear() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1, 2, 3]);
+
+    deque.append(&mut items.deque(&[4, 5, 6]));
+    let mut tail = deque.split_off(4);
+    deque.retain(|item| item.id % 2 == 0);
+    tail.append(&mut deque);
+
+    assert_eq!(items.drops(), 2);
+    assert!(deque.is_empty());
+    assert_eq!(ids(&tail), vec![5, 6, 2, 4]);
+    tail.clear();
+    assert_eq!(items.drops(), 6);
+    assert_eq!(tail.pop_front().map(|item| item.id), None);
+}
+
+#[test]
+fn zero_sized_items() {
+    let mut deque = UnsafeDeque::default();
+
+    deque.extend((0..100).map(|_| ()));
+    let mut tail = deque.split_off(30);
+    let mut cursor = tail.cursor_back_mut();
+    cursor.insert_after(());
+    cursor.move_next();
+    cursor.move_next();
+    assert_eq!(cursor.current(), None);
+    cursor.move_prev();
+    assert_eq!(cursor.remove_current(), Some(()));
+    deque.append(&mut tail);
+    let mut count = 0;
+    deque.retain(|_| {
+        count += 1;
+        count % 2 == 0
+    });
+
+    assert_eq!(deque.len(), 50);
+    assert_eq!((&deque).into_iter().count(), 50);
+    assert_eq!(deque.into_iter().rev().count(), 50);
+}
+
+#[test]
+fn drop_panicking_item_still_frees_the_rest() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1]);
+    deque.push_back(items.panicking(2));
+    deque.extend(vec![items.item(3), items.item(4)]);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(move || drop(deque)));
+
+    assert!(result.is_err());
+    assert_eq!(items.drops(), 4);
+}
+
+#[test]
+fn clear_with_panicking_item_leaves_deque_empty() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1, 2]);
+    deque.push_front(items.panicking(0));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| deque.clear()));
+
+    assert!(result.is_err());
+    assert_eq!(items.drops(), 3);
+    assert!(deque.is_empty());
+    deque.push_back(items.item(3));
+    assert_eq!(ids(&deque), vec![3]);
+}
+
+#[test]
+fn panicking_retain_predicate_keeps_deque_consistent() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1, 2, 3, 4, 5]);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        deque.retain(|item| {
+            if item.id == 4 {
+                panic!("predicate");
+            }
+            item.id != 2
+        })
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(items.drops(), 1);
+    assert_eq!(deque.len(), 4);
+    assert_eq!(ids(&deque), vec![1, 3, 4, 5]);
+    assert_eq!(deque.pop_back().map(|item| item.id), Some(5));
+}
+
+#[test]
+fn panicking_retain_drop_keeps_deque_consistent() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1]);
+    deque.push_back(items.panicking(2));
+    deque.push_back(items.item(3));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| deque.retain(|item| item.id == 3)));
+
+    assert!(result.is_err());
+    assert_eq!(items.drops(), 2);
+    assert_eq!(ids(&deque), vec![3]);
+}
+
+#[test]
+fn panicking_extend_source_keeps_pushed_items() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1]);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        deque.extend((2..5).map(|id| {
+            if id == 4 {
+                panic!("source");
+            }
+            items.item(id)
+        }))
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(deque.len(), 3);
+    assert_eq!(ids(&deque), vec![1, 2, 3]);
+    drop(deque);
+    assert_eq!(items.drops(), 3);
+}
+
+#[test]
+fn split_off_past_the_end_does_not_touch_the_deque() {
+    let items = Items::new();
+    let mut deque = items.deque(&[1, 2]);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| deque.split_off(3)));
+
+    assert!(result.is_err());
+    assert_eq!(ids(&deque), vec![1, 2]);
+}
+
+// hashes a salt shared with the test, changing it moves the key to another bucket
+#[derive(Clone)]
+struct SaltedKey {
+    id: i32,
+    salt: Rc<Cell<u32>>,
+}
+
+impl PartialEq for SaltedKey {
+    fn eq(&self, other: &SaltedKey) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for SaltedKey {}
+
+impl Hash for SaltedKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.salt.get().hash(state);
+    }
+}
+
+#[test]
+fn lru_eviction_of_a_rehashed_key_leaves_no_handle_behind() {
+    let salt = Rc::new(Cell::new(0));
+    let key = |id| SaltedKey { id, salt: salt.clone() };
+    let mut cache = LruCache::new(2);
+    cache.put(key(1), "one");
+    cache.put(key(2), "two");
+
+    salt.set(1);
+    assert_eq!(cache.pop_lru().map(|(key, value)| (key.id, value)), Some((1, "one")));
+    salt.set(0);
+
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&key(1)), None);
+    assert_eq!(cache.get(&key(2)), Some(&"two"));
+}
+
+#[test]
+fn lfu_eviction_of_a_rehashed_key_leaves_no_handle_behind() {
+    let salt = Rc::new(Cell::new(0));
+    let key = |id| SaltedKey { id, salt: salt.clone() };
+    let mut cache = LfuCache::new(2);
+    cache.put(key(1), "one");
+    cache.put(key(2), "two");
+    cache.get(&key(2));
+
+    salt.set(1);
+    assert_eq!(cache.pop_lfu().map(|(key, value)| (key.id, value)), Some((1, "one")));
+    salt.set(0);
+
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&key(1)), None);
+    assert_eq!(cache.frequency(&key(2)), Some(2));
+}
@@ -17,14 +17,14 @@ pub struct Stack<T> {
 }
 
 impl<T> Stack<T> {
-    pub(crate) fn pop(&mut self) -> Option<T> {
+    pub fn pop(&mut self) -> Option<T> {
         self.head.take().map(|mut node| {
             self.head = node.next.take();
             node.item
         })
     }
 
-    pub(crate) fn push(&mut self, item: T) {
+    pub fn push(&mut self, item: T) {
         self.head = Node::new(item, self.head.take());
     }
 }
@@ -12,3 +12,5 @@ pub mod day_11;
 pub mod day_12;
 pub mod day_13;
 pub mod day_14;
+
+pub use crate::day_14::Stack;